@@ -1,14 +1,19 @@
 use std::sync::Arc;
 
 use axum::{
-    Router,
-    extract::FromRef,
+    Json, Router,
+    extract::{FromRef, multipart::MultipartError},
     response::{IntoResponse, Response},
 };
 use jsonwebtoken::{DecodingKey, EncodingKey};
+use routes::auth::AuthError;
+use serde_json::json;
 use services::{
     container::{ContainerServiceError, ContainerServiceTrait},
+    notification::NotificationServiceTrait,
     project::{ProjectServiceError, ProjectServiceTrait},
+    schedule::{ScheduleServiceError, ScheduleServiceTrait},
+    update::UpdateServiceTrait,
 };
 use thiserror::Error;
 use tower::ServiceBuilder;
@@ -16,7 +21,7 @@ use tower_http::{
     cors::{Any, CorsLayer},
     trace::{self, TraceLayer},
 };
-use tracing::{Level, error};
+use tracing::Level;
 
 pub mod routes;
 pub mod services;
@@ -28,6 +33,15 @@ pub enum AppError {
 
     #[error(transparent)]
     Container(#[from] ContainerServiceError),
+
+    #[error(transparent)]
+    Schedule(#[from] ScheduleServiceError),
+
+    #[error(transparent)]
+    Multipart(#[from] MultipartError),
+
+    #[error(transparent)]
+    Auth(#[from] AuthError),
 }
 
 impl IntoResponse for AppError {
@@ -35,6 +49,12 @@ impl IntoResponse for AppError {
         match self {
             AppError::Project(error) => error.into_response(),
             AppError::Container(error) => error.into_response(),
+            AppError::Schedule(error) => error.into_response(),
+            AppError::Auth(error) => error.into_response(),
+            AppError::Multipart(error) => {
+                let body = Json(json!({ "error": error.body_text(), "code": "invalid_upload" }));
+                (error.status(), body).into_response()
+            }
         }
     }
 }
@@ -56,12 +76,17 @@ impl Keys {
 pub struct AdminAuth {
     pub name: String,
     pub password: String,
+    /// whether the admin sees sensitive values like passwords unmasked
+    pub reveal_secrets: bool,
 }
 
 #[derive(Clone)]
 pub struct AppState {
     project_service: Arc<dyn ProjectServiceTrait>,
     container_service: Arc<dyn ContainerServiceTrait>,
+    notification_service: Arc<dyn NotificationServiceTrait>,
+    schedule_service: Arc<dyn ScheduleServiceTrait>,
+    update_service: Arc<dyn UpdateServiceTrait>,
     jwt_keys: Arc<Keys>,
     admin_auth: Arc<AdminAuth>,
 }
@@ -78,6 +103,24 @@ impl FromRef<AppState> for Arc<dyn ContainerServiceTrait> {
     }
 }
 
+impl FromRef<AppState> for Arc<dyn NotificationServiceTrait> {
+    fn from_ref(input: &AppState) -> Self {
+        input.notification_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ScheduleServiceTrait> {
+    fn from_ref(input: &AppState) -> Self {
+        input.schedule_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn UpdateServiceTrait> {
+    fn from_ref(input: &AppState) -> Self {
+        input.update_service.clone()
+    }
+}
+
 impl FromRef<AppState> for Arc<Keys> {
     fn from_ref(input: &AppState) -> Self {
         input.jwt_keys.clone()
@@ -93,17 +136,24 @@ impl FromRef<AppState> for Arc<AdminAuth> {
 pub fn app(
     project_service: Arc<dyn ProjectServiceTrait>,
     container_service: Arc<dyn ContainerServiceTrait>,
+    notification_service: Arc<dyn NotificationServiceTrait>,
+    schedule_service: Arc<dyn ScheduleServiceTrait>,
+    update_service: Arc<dyn UpdateServiceTrait>,
     jwt_keys: Keys,
     admin_auth: AdminAuth,
 ) -> Router {
     let cors_layer = CorsLayer::new()
         .allow_headers(Any)
         .allow_origin(Any)
-        .allow_methods(Any);
+        .allow_methods(Any)
+        .expose_headers(Any);
 
     let state = AppState {
         project_service,
         container_service,
+        notification_service,
+        schedule_service,
+        update_service,
         jwt_keys: Arc::new(jwt_keys),
         admin_auth: Arc::new(admin_auth),
     };
@@ -1,11 +1,59 @@
-use std::{env, sync::Arc};
+use std::{env, sync::Arc, time::Duration};
 
 use backend::{
     AdminAuth, Keys, app,
-    services::{container::service::ContainerService, project::service::ProjectService},
+    services::{
+        container::{
+            reconciler::Reconciler, secrets::SecretsContainerService, service::ContainerService,
+        },
+        notification::{
+            service::{NotificationService, SmtpConfig, SmtpTls},
+            watcher::CrashWatcher,
+        },
+        project::{
+            ProjectServiceTrait,
+            redaction::RedactionPolicy,
+            roots::parse_roots,
+            secrets::SecretsKey,
+            service::{DEFAULT_MAX_FILE_SIZE, DEFAULT_TRASH_RETENTION, ProjectService},
+        },
+        schedule::service::ScheduleService,
+        update::{registry::RegistryClient, service::UpdateService},
+    },
 };
+use chrono::TimeDelta;
 use tracing::{info, warn};
 
+fn smtp_config() -> Option<SmtpConfig> {
+    let host = env::var("SMTP_HOST").ok()?;
+
+    let tls = match env::var("SMTP_TLS").as_deref() {
+        Ok("none") => SmtpTls::None,
+        Ok("tls") => SmtpTls::Tls,
+        _ => SmtpTls::StartTls,
+    };
+    let port = env::var("SMTP_PORT")
+        .ok()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(match tls {
+            SmtpTls::None => 25,
+            SmtpTls::StartTls => 587,
+            SmtpTls::Tls => 465,
+        });
+    let credentials = env::var("SMTP_USER")
+        .ok()
+        .zip(env::var("SMTP_PASSWORD").ok());
+    let from = env::var("SMTP_FROM").unwrap_or_else(|_| format!("containeryard@{}", host));
+
+    Some(SmtpConfig {
+        host,
+        port,
+        tls,
+        credentials,
+        from,
+    })
+}
+
 #[tokio::main]
 async fn main() {
     tracing_subscriber::fmt()
@@ -29,6 +77,51 @@ async fn main() {
         "password".to_string()
     });
 
+    // only a deployment shared with people who must not see credentials turns this off
+    let reveal_secrets = env::var("ADMIN_REVEAL_SECRETS")
+        .map(|reveal| reveal != "false")
+        .unwrap_or(true);
+    let redaction_policy = env::var("REDACT_KEYS")
+        .map(|keys| RedactionPolicy::new(keys.split(',').map(str::to_string)))
+        .unwrap_or_default();
+
+    let max_upload_size = env::var("MAX_UPLOAD_SIZE")
+        .ok()
+        .and_then(|bytes| bytes.parse().ok())
+        .unwrap_or(DEFAULT_MAX_FILE_SIZE);
+
+    // 0 keeps deleted projects and files until they are purged by hand
+    let trash_retention = match env::var("TRASH_RETENTION_DAYS")
+        .ok()
+        .and_then(|days| days.parse().ok())
+    {
+        Some(0) => None,
+        Some(days) => Some(TimeDelta::days(days)),
+        None => Some(DEFAULT_TRASH_RETENTION),
+    };
+
+    let crash_check_interval = env::var("CRASH_CHECK_INTERVAL")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(60);
+
+    let reconcile_interval = env::var("RECONCILE_INTERVAL")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(60 * 5);
+    let update_check_interval = env::var("UPDATE_CHECK_INTERVAL")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(60 * 60 * 6);
+    let insecure_registries = env::var("INSECURE_REGISTRIES")
+        .map(|registries| {
+            registries
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port))
         .await
         .unwrap();
@@ -36,15 +129,111 @@ async fn main() {
     info!("listening on {}", listener.local_addr().unwrap());
     info!("using project path '{}'", project_dir.clone());
 
+    let mut project_service = ProjectService::new(project_dir.into())
+        .with_max_file_size(max_upload_size)
+        .with_trash_retention(trash_retention)
+        .with_redaction_policy(redaction_policy);
+    // more roots next to PROJECT_DIR, e.g. `infra=/opt/infra:ro`
+    if let Ok(roots) = env::var("PROJECT_ROOTS") {
+        match parse_roots(&roots) {
+            Ok(roots) => {
+                for root in roots {
+                    info!(
+                        "using project root {} at '{}'",
+                        root.name,
+                        root.path.display()
+                    );
+                    project_service = project_service.with_root(root);
+                }
+            }
+            Err(err) => warn!("ignoring PROJECT_ROOTS - {}", err),
+        }
+    }
+    if let Ok(template_dir) = env::var("TEMPLATE_DIR") {
+        info!("using template path '{}'", template_dir);
+        project_service = project_service.with_template_dir(template_dir.into());
+    }
+    match env::var("SECRETS_KEY") {
+        Ok(secrets_key) => {
+            project_service = project_service.with_secrets_key(SecretsKey::new(&secrets_key))
+        }
+        Err(_) => warn!("no secrets key was set, secrets are disabled!"),
+    }
+    let project_service = Arc::new(project_service);
+    let container_service = Arc::new(SecretsContainerService::new(
+        Arc::new(ContainerService),
+        project_service.clone(),
+    ));
+    let notification_service = Arc::new(NotificationService::new(
+        project_service.clone(),
+        smtp_config(),
+    ));
+
+    let schedule_service = Arc::new(ScheduleService::new(
+        project_service.clone(),
+        container_service.clone(),
+        notification_service.clone(),
+    ));
+
+    let update_service = Arc::new(UpdateService::new(
+        project_service.clone(),
+        container_service.clone(),
+        notification_service.clone(),
+        RegistryClient::new(insecure_registries),
+    ));
+
+    tokio::spawn(
+        Reconciler::new(
+            project_service.clone(),
+            container_service.clone(),
+            notification_service.clone(),
+        )
+        .run(Duration::from_secs(reconcile_interval)),
+    );
+    tokio::spawn(schedule_service.clone().run(Duration::from_secs(30)));
+    tokio::spawn({
+        let project_service = project_service.clone();
+        async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+            loop {
+                interval.tick().await;
+                match project_service.purge_expired() {
+                    Ok(purged) if !purged.is_empty() => {
+                        info!("purged {} expired items from the trash", purged.len())
+                    }
+                    Ok(_) => {}
+                    Err(err) => warn!("failed to purge the trash - {}", err),
+                }
+            }
+        }
+    });
+    tokio::spawn(
+        update_service
+            .clone()
+            .run(Duration::from_secs(update_check_interval)),
+    );
+    tokio::spawn(
+        CrashWatcher::new(
+            project_service.clone(),
+            container_service.clone(),
+            notification_service.clone(),
+        )
+        .run(Duration::from_secs(crash_check_interval)),
+    );
+
     axum::serve(
         listener,
         app(
-            Arc::new(ProjectService::new(project_dir.into())),
-            Arc::new(ContainerService),
+            project_service,
+            container_service,
+            notification_service,
+            schedule_service,
+            update_service,
             Keys::new(secret.as_bytes()),
             AdminAuth {
                 name: admin_name,
                 password: admin_password,
+                reveal_secrets,
             },
         ),
     )
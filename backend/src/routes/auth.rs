@@ -31,17 +31,21 @@ pub enum AuthError {
 
     #[error("Invalid token")]
     InvalidToken,
+
+    #[error("Missing permission to {0}")]
+    MissingPermission(&'static str),
 }
 
 impl IntoResponse for AuthError {
     fn into_response(self) -> Response {
-        let status = match self {
-            AuthError::WrongCredentials => StatusCode::UNAUTHORIZED,
-            AuthError::MissingCredentials => StatusCode::BAD_REQUEST,
-            AuthError::TokenCreation => StatusCode::INTERNAL_SERVER_ERROR,
-            AuthError::InvalidToken => StatusCode::UNAUTHORIZED,
+        let (status, code) = match self {
+            AuthError::WrongCredentials => (StatusCode::UNAUTHORIZED, "wrong_credentials"),
+            AuthError::MissingCredentials => (StatusCode::BAD_REQUEST, "missing_credentials"),
+            AuthError::TokenCreation => (StatusCode::INTERNAL_SERVER_ERROR, "token_creation"),
+            AuthError::InvalidToken => (StatusCode::UNAUTHORIZED, "invalid_token"),
+            AuthError::MissingPermission(_) => (StatusCode::FORBIDDEN, "missing_permission"),
         };
-        let body = Json(json!({ "error": self.to_string() }));
+        let body = Json(json!({ "error": self.to_string(), "code": code }));
         (status, body).into_response()
     }
 }
@@ -85,6 +89,7 @@ async fn authorize(
         sub: "admin".to_owned(),
         iat: now,
         exp: now + 60 * 60 * 24 * 30, // 1 month,
+        reveal_secrets: admin_auth.reveal_secrets,
     };
 
     let token = encode(&Header::default(), &claims, &keys.encoding)
@@ -98,6 +103,9 @@ pub struct Claims {
     sub: String,
     iat: u64,
     exp: u64,
+    /// sensitive values are masked without this permission
+    #[serde(default)]
+    pub reveal_secrets: bool,
 }
 
 impl FromRequestParts<AppState> for Claims {
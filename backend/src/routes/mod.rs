@@ -4,9 +4,15 @@ use crate::AppState;
 
 pub mod auth;
 pub mod projects;
+pub mod roots;
+pub mod templates;
+pub mod trash;
 
 pub fn routes(state: AppState) -> Router {
     Router::new()
         .nest("/auth", auth::routes(state.clone()))
         .nest("/projects", projects::routes(state.clone()))
+        .nest("/roots", roots::routes(state.clone()))
+        .nest("/templates", templates::routes(state.clone()))
+        .nest("/trash", trash::routes(state.clone()))
 }
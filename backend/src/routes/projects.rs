@@ -1,11 +1,18 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use futures::{StreamExt, stream};
+use tokio_util::io::{ReaderStream, SyncIoBridge};
+use tracing::error;
 
 use axum::{
     Json, Router,
-    extract::{self, Path, Query, State},
+    body::{Body, Bytes},
+    extract::{self, DefaultBodyLimit, Multipart, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
     middleware::from_extractor_with_state,
     response::IntoResponse,
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
 };
 use serde::Deserialize;
 use serde_json::{Value, json};
@@ -13,48 +20,255 @@ use serde_json::{Value, json};
 use crate::{
     AppError, AppState,
     services::{
-        container::ContainerServiceTrait,
-        project::{ProjectInfo, ProjectServiceTrait},
+        container::{ContainerServiceTrait, DownOptions, ProjectStatus, RemoveImages},
+        notification::{NotificationEvent, NotificationServiceTrait, NotificationSettings},
+        project::{
+            DesiredState, NameConflict, ProjectInfo, ProjectServiceError, ProjectServiceTrait,
+            ProjectSettings, dependencies,
+            env::{DEFAULT_ENV_FILE, EnvEntry, EnvFile},
+            metadata::ProjectMetadata,
+            redaction::MASK,
+            removal::{self, DeleteStepStatus},
+            revision,
+            roots::RootAction,
+            service::io_error,
+        },
+        schedule::{NewSchedule, ScheduleAction, ScheduleServiceTrait},
+        update::UpdateServiceTrait,
     },
 };
 
-use super::auth::Claims;
+use super::auth::{AuthError, Claims};
+
+/// Room for the multipart boundaries and headers around an uploaded file
+const UPLOAD_OVERHEAD: usize = 64 * 1024;
 
 pub fn routes(state: AppState) -> Router {
+    let upload_limit = state
+        .project_service
+        .max_file_size()
+        .saturating_add(UPLOAD_OVERHEAD);
+
     Router::new()
         .route("/", get(get_all_projects))
         .route("/{project_name}", get(get_project_details))
         .route("/{project_name}", post(post_update_project_file))
         .route("/{project_name}", delete(delete_project))
+        .route("/{project_name}/tree", get(get_project_tree))
+        .route("/{project_name}/files", get(get_project_files))
+        .route(
+            "/{project_name}/upload",
+            post(post_upload_project_files).layer(DefaultBodyLimit::max(upload_limit)),
+        )
+        .route("/{project_name}/download", get(get_download_project_file))
+        .route("/{project_name}/export", get(get_export_project))
+        .route(
+            "/import",
+            post(post_import_project).layer(DefaultBodyLimit::max(upload_limit)),
+        )
+        .route("/{project_name}/dirs", post(post_create_project_dir))
+        .route("/{project_name}/dirs", delete(delete_project_dir))
+        .route(
+            "/{project_name}/notifications",
+            get(get_project_notifications),
+        )
+        .route(
+            "/{project_name}/notifications",
+            put(put_project_notifications),
+        )
+        .route("/{project_name}/schedules", get(get_project_schedules))
+        .route("/{project_name}/schedules", post(post_create_schedule))
+        .route("/{project_name}/schedules/{id}", put(put_update_schedule))
+        .route("/{project_name}/schedules/{id}", delete(delete_schedule))
+        .route("/{project_name}/updates", get(get_project_updates))
+        .route("/{project_name}/updates", put(put_project_updates))
+        .route("/{project_name}/env", get(get_project_env))
+        .route("/{project_name}/env", put(put_project_env))
+        .route("/{project_name}/metadata", get(get_project_metadata))
+        .route("/{project_name}/metadata", put(put_project_metadata))
+        .route("/{project_name}/secrets", get(get_project_secrets))
+        .route("/{project_name}/secrets/{name}", put(put_project_secret))
+        .route(
+            "/{project_name}/secrets/{name}",
+            delete(delete_project_secret),
+        )
+        .route(
+            "/{project_name}/dependencies",
+            get(get_project_dependencies),
+        )
+        .route(
+            "/{project_name}/dependencies",
+            put(put_project_dependencies),
+        )
         .route("/stop/{project_name}", post(post_stop_project))
         .route("/start/{project_name}", post(post_start_project))
         .route("/restart/{project_name}", post(post_restart_project))
         .route("/create/{project_name}", post(post_create_project))
+        .route("/rename/{project_name}", post(post_rename_project))
+        .route("/duplicate/{project_name}", post(post_duplicate_project))
+        .route("/start-all", post(post_start_all_projects))
+        .route("/bulk", post(post_bulk_action))
         .route_layer(from_extractor_with_state::<Claims, _>(state.clone()))
         .with_state(state)
 }
 
+#[derive(Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum ProjectSort {
+    #[default]
+    Name,
+    Status,
+    Owner,
+}
+
+#[derive(Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Deserialize)]
+struct ProjectsQuery {
+    /// only lists projects whose name contains this, ignoring case
+    search: Option<String>,
+    status: Option<ProjectStatus>,
+    /// only lists projects with this tag
+    tag: Option<String>,
+    #[serde(default)]
+    sort: ProjectSort,
+    #[serde(default)]
+    order: SortOrder,
+    /// `X-Next-Cursor` of the previous page, it only fits the same sort and order
+    cursor: Option<String>,
+    /// all matching projects are listed without it
+    limit: Option<usize>,
+}
+
+/// Position of a project in the sorted list - the value it is sorted by and its name
+type SortPosition = (String, String);
+
+fn encode_cursor(position: &SortPosition) -> String {
+    URL_SAFE_NO_PAD.encode(json!([position.0, position.1]).to_string())
+}
+
+fn decode_cursor(cursor: &str) -> Result<SortPosition, ProjectServiceError> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or_else(|| ProjectServiceError::InvalidCursor(cursor.to_string()))
+}
+
+/// Settings of a project inside a listing - a broken settings file only loses the settings
+/// of its own project instead of failing the whole request
+fn settings_or_default(
+    project_service: &dyn ProjectServiceTrait,
+    project: &ProjectInfo,
+) -> ProjectSettings {
+    project_service.settings(project).unwrap_or_else(|err| {
+        error!("failed to read the settings of {} - {}", project.name, err);
+        ProjectSettings::default()
+    })
+}
+
+/// Lists the projects matching the query, one page at a time if a `limit` is set.
+/// The number of all matching projects is returned as `X-Total-Count`
 async fn get_all_projects(
     State(project_service): State<Arc<dyn ProjectServiceTrait>>,
     State(container_service): State<Arc<dyn ContainerServiceTrait>>,
+    State(update_service): State<Arc<dyn UpdateServiceTrait>>,
+    Query(query): Query<ProjectsQuery>,
 ) -> Result<impl IntoResponse, AppError> {
-    let projects = project_service.all_projects()?;
-    let are_online = container_service.are_online(&projects)?;
+    let search = query.search.as_deref().map(str::to_lowercase);
 
-    let objects: Vec<Value> = projects
+    let mut projects = Vec::new();
+    let mut settings = Vec::new();
+    for project in project_service.all_projects()? {
+        if let Some(search) = &search
+            && !project.name.to_lowercase().contains(search)
+        {
+            continue;
+        }
+
+        let project_settings = settings_or_default(project_service.as_ref(), &project);
+        if let Some(tag) = &query.tag
+            && !project_settings.metadata.has_tag(tag)
+        {
+            continue;
+        }
+
+        projects.push(project);
+        settings.push(project_settings);
+    }
+
+    let statuses = container_service.statuses(&projects)?;
+
+    let mut rows = projects
         .into_iter()
-        .zip(are_online.into_iter())
-        .map(|(project, is_online)| {
-            let status = if is_online { "running" } else { "stopped" };
+        .zip(settings)
+        .zip(statuses)
+        .filter(|(_, status)| query.status.is_none_or(|wanted| wanted == *status))
+        .map(|((project, settings), status)| {
+            let key = match query.sort {
+                ProjectSort::Name => String::new(),
+                // the rank keeps running before partial before stopped
+                ProjectSort::Status => (status as u8).to_string(),
+                ProjectSort::Owner => settings.metadata.owner.clone().unwrap_or_default(),
+            };
+            ((key, project.name.to_string()), project, settings, status)
+        })
+        .collect::<Vec<_>>();
+
+    rows.sort_by(|a, b| a.0.cmp(&b.0));
+    if query.order == SortOrder::Desc {
+        rows.reverse();
+    }
+
+    let total = rows.len();
+
+    if let Some(cursor) = &query.cursor {
+        let after = decode_cursor(cursor)?;
+        rows.retain(|(position, ..)| match query.order {
+            SortOrder::Asc => *position > after,
+            SortOrder::Desc => *position < after,
+        });
+    }
+
+    let mut next_cursor = None;
+    if let Some(limit) = query.limit
+        && rows.len() > limit
+    {
+        rows.truncate(limit);
+        next_cursor = rows.last().map(|(position, ..)| encode_cursor(position));
+    }
+
+    let objects: Vec<Value> = rows
+        .into_iter()
+        .map(|(_, project, settings, status)| {
+            let desired_state = settings.desired_state;
+            let drift = desired_state
+                .is_some_and(|state| (state == DesiredState::Running) != status.is_online());
 
             json!({
                 "name": project.name,
-                "status": status
+                "status": status,
+                "desired_state": desired_state,
+                "drift": drift,
+                "update_available": update_service.update_available(&project),
+                "metadata": settings.metadata,
             })
         })
         .collect();
 
-    Ok(Json(json!(objects)))
+    let mut headers = HeaderMap::new();
+    headers.insert("x-total-count", total.into());
+    if let Some(cursor) = next_cursor.and_then(|cursor| cursor.parse().ok()) {
+        headers.insert("x-next-cursor", cursor);
+    }
+
+    Ok((headers, Json(json!(objects))))
 }
 
 fn project_details(
@@ -86,60 +300,265 @@ async fn get_project_details(
     State(container_service): State<Arc<dyn ContainerServiceTrait>>,
     Path(project_name): Path<String>,
     Query(query): Query<FileQuery>,
+    claims: Claims,
 ) -> Result<impl IntoResponse, AppError> {
     let project_info = project_service.project(&project_name)?;
 
     if let Some(file) = query.file {
         let content = project_service.read_file(&project_info, &file)?;
 
-        return Ok(Json(json!({
-            "name": file,
-            "content": content,
-        }))
-        .into_response());
+        return Ok((
+            [(header::ETAG, etag(&content))],
+            Json(json!({
+                "name": file,
+                "content": redact(project_service.as_ref(), &claims, &content),
+            })),
+        )
+            .into_response());
     }
 
     let json = project_details(&project_info, project_service, container_service)?;
     Ok(Json(json).into_response())
 }
 
-async fn delete_project(
+async fn get_project_tree(
     State(project_service): State<Arc<dyn ProjectServiceTrait>>,
     Path(project_name): Path<String>,
-    Query(query): Query<FileQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let tree = project_service.tree(&project_info)?;
+
+    Ok(Json(tree))
+}
+
+async fn get_project_files(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+
+    // one unreadable file doesn't hide the others
+    let files: Vec<Value> = project_service
+        .files(&project_info)?
+        .iter()
+        .map(
+            |file| match project_service.file_metadata(&project_info, file) {
+                Ok(metadata) => json!(metadata),
+                Err(err) => json!({ "path": file, "error": err.to_string() }),
+            },
+        )
+        .collect();
+
+    Ok(Json(files))
+}
+
+#[derive(Deserialize)]
+struct DirQuery {
+    dir: String,
+}
+
+async fn post_create_project_dir(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+    Query(query): Query<DirQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    project_service.create_dir(&project_info, &query.dir)?;
+
+    let tree = project_service.tree(&project_info)?;
+    Ok(Json(tree))
+}
+
+async fn delete_project_dir(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+    Query(query): Query<DirQuery>,
 ) -> Result<(), AppError> {
     let project_info = project_service.project(&project_name)?;
+    project_service.delete_dir(&project_info, &query.dir)?;
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct DeleteQuery {
+    file: Option<String>,
+    /// stop a running project before deleting it
+    #[serde(default)]
+    force: bool,
+    /// run `docker compose down` first, also for a stopped project
+    #[serde(default)]
+    down: bool,
+    #[serde(default)]
+    volumes: bool,
+    rmi: Option<RemoveImages>,
+}
+
+/// Moves the project or one of its files into the trash
+async fn delete_project(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(container_service): State<Arc<dyn ContainerServiceTrait>>,
+    Path(project_name): Path<String>,
+    Query(query): Query<DeleteQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
 
     if let Some(file) = query.file {
         project_service.delete_file(&project_info, &file)?;
-    } else {
-        project_service.delete(&project_info)?;
+        return Ok(().into_response());
     }
 
-    Ok(())
+    // nothing is torn down if the project can't be moved into the trash afterwards
+    project_service.check_permission(&project_name, RootAction::Delete)?;
+
+    // removing volumes or images only makes sense as part of a down
+    let down = (query.down || query.volumes || query.rmi.is_some()).then_some(DownOptions {
+        volumes: query.volumes,
+        rmi: query.rmi,
+    });
+
+    let is_online = container_service.is_online(&project_info)?;
+    if is_online && !query.force && down.is_none() {
+        return Err(ProjectServiceError::ProjectRunning(project_name).into());
+    }
+
+    // a forced delete still has to stop the containers
+    let down = down.or(is_online.then_some(DownOptions::default()));
+
+    let dependents = dependencies::dependents(project_service.as_ref(), &project_info)?;
+
+    let steps = removal::delete_project(
+        project_service.as_ref(),
+        container_service.as_ref(),
+        &project_info,
+        down.as_ref(),
+    );
+
+    if steps.iter().all(|step| step.status == DeleteStepStatus::Ok) {
+        let mut json = json!({ "name": project_name, "steps": steps });
+        if !dependents.is_empty() {
+            json["warnings"] = dependents
+                .iter()
+                .map(|dependent| {
+                    format!(
+                        "{} depends on {} and can't be started until the dependency is removed",
+                        dependent.name, project_name
+                    )
+                })
+                .collect();
+        }
+        return Ok(Json(json).into_response());
+    }
+
+    let body = json!({
+        "error": format!("Project {} was not deleted completely", project_name),
+        "code": "delete_incomplete",
+        "name": project_name,
+        "steps": steps,
+    });
+    Ok((StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response())
+}
+
+#[derive(Deserialize)]
+struct StopQuery {
+    #[serde(default)]
+    cascade: bool,
 }
 
 async fn post_stop_project(
     State(project_service): State<Arc<dyn ProjectServiceTrait>>,
     State(container_service): State<Arc<dyn ContainerServiceTrait>>,
+    State(notification_service): State<Arc<dyn NotificationServiceTrait>>,
     Path(project_name): Path<String>,
+    Query(query): Query<StopQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     let project_info = project_service.project(&project_name)?;
 
-    container_service.stop(&project_info)?;
+    let mut running_dependents = Vec::new();
+    for dependent in dependencies::dependents(project_service.as_ref(), &project_info)? {
+        if container_service.is_online(&dependent)? {
+            running_dependents.push(dependent);
+        }
+    }
+
+    if query.cascade {
+        for dependent in running_dependents.drain(..) {
+            project_service.set_desired_state(&dependent, DesiredState::Stopped)?;
+
+            let result = container_service.stop(&dependent);
+            notification_service.notify_outcome(&dependent, NotificationEvent::Stopped, &result);
+            result?;
+        }
+    }
+
+    project_service.set_desired_state(&project_info, DesiredState::Stopped)?;
+
+    let result = container_service.stop(&project_info);
+    notification_service.notify_outcome(&project_info, NotificationEvent::Stopped, &result);
+    result?;
+
+    let mut json = project_details(&project_info, project_service, container_service)?;
+    if !running_dependents.is_empty() {
+        json["warnings"] = running_dependents
+            .iter()
+            .map(|dependent| {
+                format!(
+                    "{} depends on {} and is still running",
+                    dependent.name, project_info.name
+                )
+            })
+            .collect();
+    }
 
-    let json = project_details(&project_info, project_service, container_service)?;
     Ok(Json(json).into_response())
 }
 
+/// Starts the dependencies of `project_info` which are not running yet, in dependency order
+fn start_dependencies(
+    project_info: &ProjectInfo,
+    project_service: &Arc<dyn ProjectServiceTrait>,
+    container_service: &Arc<dyn ContainerServiceTrait>,
+    notification_service: &Arc<dyn NotificationServiceTrait>,
+) -> Result<(), AppError> {
+    let order =
+        dependencies::start_order(project_service.as_ref(), std::slice::from_ref(project_info))?;
+
+    for dependency in order
+        .iter()
+        .filter(|dependency| dependency.name != project_info.name)
+    {
+        if container_service.is_online(dependency)? {
+            continue;
+        }
+
+        project_service.set_desired_state(dependency, DesiredState::Running)?;
+
+        let result = container_service.start(dependency);
+        notification_service.notify_outcome(dependency, NotificationEvent::Started, &result);
+        result?;
+    }
+
+    Ok(())
+}
+
 async fn post_start_project(
     State(project_service): State<Arc<dyn ProjectServiceTrait>>,
     State(container_service): State<Arc<dyn ContainerServiceTrait>>,
+    State(notification_service): State<Arc<dyn NotificationServiceTrait>>,
     Path(project_name): Path<String>,
 ) -> Result<impl IntoResponse, AppError> {
     let project_info = project_service.project(&project_name)?;
+    start_dependencies(
+        &project_info,
+        &project_service,
+        &container_service,
+        &notification_service,
+    )?;
+    project_service.set_desired_state(&project_info, DesiredState::Running)?;
 
-    container_service.start(&project_info)?;
+    let result = container_service.start(&project_info);
+    notification_service.notify_outcome(&project_info, NotificationEvent::Started, &result);
+    result?;
 
     let json = project_details(&project_info, project_service, container_service)?;
     Ok(Json(json).into_response())
@@ -148,17 +567,204 @@ async fn post_start_project(
 async fn post_restart_project(
     State(project_service): State<Arc<dyn ProjectServiceTrait>>,
     State(container_service): State<Arc<dyn ContainerServiceTrait>>,
+    State(notification_service): State<Arc<dyn NotificationServiceTrait>>,
     Path(project_name): Path<String>,
 ) -> Result<impl IntoResponse, AppError> {
     let project_info = project_service.project(&project_name)?;
+    start_dependencies(
+        &project_info,
+        &project_service,
+        &container_service,
+        &notification_service,
+    )?;
+    project_service.set_desired_state(&project_info, DesiredState::Running)?;
 
-    container_service.pull(&project_info)?;
-    container_service.start(&project_info)?;
+    let result = container_service
+        .pull(&project_info)
+        .and_then(|_| container_service.start(&project_info));
+    notification_service.notify_outcome(&project_info, NotificationEvent::Started, &result);
+    result?;
 
     let json = project_details(&project_info, project_service, container_service)?;
     Ok(Json(json).into_response())
 }
 
+/// Starts every project after its dependencies. Projects whose dependencies are missing or
+/// failed to start are skipped
+async fn post_start_all_projects(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(container_service): State<Arc<dyn ContainerServiceTrait>>,
+    State(notification_service): State<Arc<dyn NotificationServiceTrait>>,
+) -> Result<impl IntoResponse, AppError> {
+    let projects = project_service.all_projects()?;
+    let order = dependencies::start_order_skipping_missing(project_service.as_ref(), &projects)?;
+
+    let mut failed = Vec::new();
+    let mut results = Vec::new();
+
+    for project in order {
+        let missing_dependency =
+            dependencies::missing_dependency(project_service.as_ref(), &project)
+                .unwrap_or_default();
+        let failed_dependency = settings_or_default(project_service.as_ref(), &project)
+            .dependencies
+            .into_iter()
+            .find(|dependency| failed.contains(dependency));
+
+        let result = match (missing_dependency, failed_dependency) {
+            (Some(dependency), _) => Err(ProjectServiceError::DependencyNotFound {
+                project: project.name.to_string(),
+                dependency,
+            }
+            .to_string()),
+            (None, Some(dependency)) => Err(format!("dependency {} failed to start", dependency)),
+            (None, None) if container_service.is_online(&project)? => Ok(()),
+            (None, None) => {
+                project_service.set_desired_state(&project, DesiredState::Running)?;
+
+                let result = container_service.start(&project);
+                notification_service.notify_outcome(&project, NotificationEvent::Started, &result);
+                result.map_err(|err| err.to_string())
+            }
+        };
+
+        results.push(match result {
+            Ok(()) => json!({ "name": project.name, "status": "running" }),
+            Err(error) => {
+                failed.push(project.name.to_string());
+                json!({ "name": project.name, "status": "stopped", "error": error })
+            }
+        });
+    }
+
+    Ok(Json(json!(results)))
+}
+
+fn bulk_concurrency_default() -> usize {
+    4
+}
+
+#[derive(Deserialize)]
+struct BulkAction {
+    action: ScheduleAction,
+    #[serde(default)]
+    projects: Vec<String>,
+    /// selects all projects whose name matches, e.g. `web-*`
+    pattern: Option<String>,
+    /// selects all projects with this tag
+    tag: Option<String>,
+    #[serde(default = "bulk_concurrency_default")]
+    concurrency: usize,
+}
+
+/// Runs `action` on a single project of a bulk request and describes the outcome
+fn run_bulk_action(
+    action: ScheduleAction,
+    project: &ProjectInfo,
+    project_service: &dyn ProjectServiceTrait,
+    container_service: &dyn ContainerServiceTrait,
+    notification_service: &dyn NotificationServiceTrait,
+) -> Value {
+    let result = (|| -> Result<(), AppError> {
+        if let Some(state) = action.desired_state() {
+            project_service.set_desired_state(project, state)?;
+        }
+
+        let result = action.execute(container_service, project);
+        if let Some(event) = action.event() {
+            notification_service.notify_outcome(project, event, &result);
+        }
+
+        Ok(result?)
+    })();
+
+    let status = container_service
+        .is_online(project)
+        .ok()
+        .map(|is_online| if is_online { "running" } else { "stopped" });
+
+    match result {
+        Ok(()) => json!({ "name": project.name, "status": status }),
+        Err(err) => json!({ "name": project.name, "status": status, "error": err.to_string() }),
+    }
+}
+
+/// Runs an action on several projects, at most `concurrency` at a time.
+/// Dependencies are started before and stopped after their dependents
+async fn post_bulk_action(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(container_service): State<Arc<dyn ContainerServiceTrait>>,
+    State(notification_service): State<Arc<dyn NotificationServiceTrait>>,
+    extract::Json(bulk): extract::Json<BulkAction>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut results = Vec::new();
+    let mut selected: Vec<ProjectInfo> = Vec::new();
+
+    for name in &bulk.projects {
+        match project_service.project(name) {
+            Ok(project) if !selected.contains(&project) => selected.push(project),
+            Ok(_) => {}
+            Err(err) => results.push(json!({ "name": name, "error": err.to_string() })),
+        }
+    }
+
+    if let Some(pattern) = &bulk.pattern {
+        for project in project_service.all_projects()? {
+            if project.matches(pattern) && !selected.contains(&project) {
+                selected.push(project);
+            }
+        }
+    }
+
+    if let Some(tag) = &bulk.tag {
+        for project in project_service.all_projects()? {
+            if settings_or_default(project_service.as_ref(), &project)
+                .metadata
+                .has_tag(tag)
+                && !selected.contains(&project)
+            {
+                selected.push(project);
+            }
+        }
+    }
+
+    let mut levels = dependencies::start_levels(project_service.as_ref(), &selected)?;
+    if bulk.action == ScheduleAction::Stop {
+        levels.reverse();
+    }
+
+    for level in levels {
+        let level_results: Vec<Value> = stream::iter(level)
+            .map(|project| {
+                let project_service = project_service.clone();
+                let container_service = container_service.clone();
+                let notification_service = notification_service.clone();
+                let name = project.name.to_string();
+
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        run_bulk_action(
+                            bulk.action,
+                            &project,
+                            project_service.as_ref(),
+                            container_service.as_ref(),
+                            notification_service.as_ref(),
+                        )
+                    })
+                    .await
+                    .unwrap_or_else(|err| json!({ "name": name, "error": err.to_string() }))
+                }
+            })
+            .buffered(bulk.concurrency.max(1))
+            .collect()
+            .await;
+
+        results.extend(level_results);
+    }
+
+    Ok(Json(json!(results)))
+}
+
 #[derive(Deserialize)]
 struct UpdateFile {
     content: String,
@@ -169,29 +775,640 @@ struct FileUpdateQuery {
     file: String,
 }
 
+/// Masks sensitive values unless the user may reveal them
+fn redact(project_service: &dyn ProjectServiceTrait, claims: &Claims, content: &str) -> String {
+    if claims.reveal_secrets {
+        content.to_string()
+    } else {
+        project_service.redaction_policy().mask(content)
+    }
+}
+
+fn etag(content: &str) -> String {
+    format!("\"{}\"", revision(content.as_bytes()))
+}
+
+/// Revisions listed in an `If-Match` header, weak tags are compared like strong ones
+fn if_match_revisions(headers: &HeaderMap) -> Option<Vec<String>> {
+    let value = headers.get(header::IF_MATCH)?.to_str().ok()?;
+
+    let revisions = value
+        .split(',')
+        .map(|tag| {
+            tag.trim()
+                .trim_start_matches("W/")
+                .trim_matches('"')
+                .to_string()
+        })
+        .collect();
+
+    Some(revisions)
+}
+
 async fn post_update_project_file(
     State(project_service): State<Arc<dyn ProjectServiceTrait>>,
     Path(project_name): Path<String>,
     Query(query): Query<FileUpdateQuery>,
+    headers: HeaderMap,
+    claims: Claims,
     extract::Json(update): extract::Json<UpdateFile>,
 ) -> Result<impl IntoResponse, AppError> {
     let project_info = project_service.project(&project_name)?;
-    let content = project_service.update_file(&project_info, &query.file, &update.content)?;
 
-    Ok(Json(json!({
-        "name": query.file,
-        "content": content,
-    }))
-    .into_response())
+    // values still masked keep what the file holds, the user never saw them
+    let new_content = if claims.reveal_secrets {
+        update.content
+    } else {
+        let current = match project_service.read_file(&project_info, &query.file) {
+            Err(ProjectServiceError::FileNotFound { .. }) => String::new(),
+            result => result?,
+        };
+        project_service
+            .redaction_policy()
+            .unmask(&update.content, &current)
+    };
+
+    let content = match if_match_revisions(&headers) {
+        Some(revisions) => project_service
+            .update_file_if_match(&project_info, &query.file, &new_content, &revisions)
+            .map_err(|err| match err {
+                ProjectServiceError::FileChanged {
+                    file,
+                    revision,
+                    content,
+                } => ProjectServiceError::FileChanged {
+                    file,
+                    revision,
+                    content: content
+                        .map(|content| redact(project_service.as_ref(), &claims, &content)),
+                },
+                err => err,
+            })?,
+        None => project_service.update_file(&project_info, &query.file, &new_content)?,
+    };
+
+    Ok((
+        [(header::ETAG, etag(&content))],
+        Json(json!({
+            "name": query.file,
+            "content": redact(project_service.as_ref(), &claims, &content),
+        })),
+    )
+        .into_response())
+}
+
+#[derive(Deserialize)]
+struct UploadQuery {
+    dir: Option<String>,
+}
+
+/// Stores every file of a multipart upload under its file name, optionally inside `dir`
+async fn post_upload_project_files(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+    Query(query): Query<UploadQuery>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let max_size = project_service.max_file_size();
+
+    let mut uploaded = Vec::new();
+    while let Some(mut field) = multipart.next_field().await? {
+        let Some(file_name) = field.file_name().map(|name| name.to_string()) else {
+            continue;
+        };
+
+        let file = match &query.dir {
+            Some(dir) => format!("{}/{}", dir.trim_end_matches('/'), file_name),
+            None => file_name,
+        };
+
+        let mut content = Vec::new();
+        while let Some(chunk) = field.chunk().await? {
+            content.extend_from_slice(&chunk);
+
+            if content.len() > max_size {
+                return Err(ProjectServiceError::FileTooLarge { file, max_size }.into());
+            }
+        }
+
+        project_service.write_bytes(&project_info, &file, &content)?;
+        uploaded.push(json!({ "name": file, "size": content.len() }));
+    }
+
+    Ok(Json(json!(uploaded)))
+}
+
+/// Streams the raw content of a file, e.g. a certificate or an image
+async fn get_download_project_file(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+    Query(query): Query<FileUpdateQuery>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let path = project_service.file_path(&project_info, &query.file)?;
+
+    // text files are masked like in the editor, binary files hold no assignments to mask
+    let masked = match claims.reveal_secrets {
+        true => None,
+        false => match project_service.read_file(&project_info, &query.file) {
+            Ok(content) => Some(redact(project_service.as_ref(), &claims, &content)),
+            Err(ProjectServiceError::BinaryFile(_)) => None,
+            Err(err) => return Err(err.into()),
+        },
+    };
+
+    let file = tokio::fs::File::open(&path)
+        .await
+        .map_err(|err| io_error(&path, err, ProjectServiceError::FailedToReadFile))?;
+    let size = file
+        .metadata()
+        .await
+        .map_err(|err| io_error(&path, err, ProjectServiceError::FailedToReadFile))?
+        .len();
+
+    let content_type = mime_guess::from_path(&path).first_or_octet_stream();
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .replace('"', "_");
+    let size = masked
+        .as_ref()
+        .map(|content| content.len() as u64)
+        .unwrap_or(size);
+
+    let headers = [
+        (header::CONTENT_TYPE, content_type.to_string()),
+        (header::CONTENT_LENGTH, size.to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", file_name),
+        ),
+    ];
+
+    let body = match masked {
+        Some(content) => Body::from(content),
+        None => Body::from_stream(ReaderStream::new(file)),
+    };
+
+    Ok((headers, body))
 }
 
+#[derive(Deserialize)]
+struct ExportQuery {
+    /// leave out `.env` files, e.g. to move a stack without its credentials
+    #[serde(default)]
+    exclude_env: bool,
+}
+
+/// Streams the project directory as tar.gz while it is packed
+async fn get_export_project(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+    Query(query): Query<ExportQuery>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError> {
+    // an archive can't be masked, it holds all files as they are
+    if !claims.reveal_secrets {
+        return Err(AuthError::MissingPermission("reveal secrets").into());
+    }
+
+    let project_info = project_service.project(&project_name)?;
+    let file_name = format!("{}.tar.gz", project_info.name.replace('"', "_"));
+
+    let (reader, writer) = tokio::io::duplex(64 * 1024);
+    tokio::task::spawn_blocking(move || {
+        let mut writer = SyncIoBridge::new(writer);
+        if let Err(err) = project_service.export(&project_info, query.exclude_env, &mut writer) {
+            error!("failed to export {} - {}", project_info.name, err);
+        }
+    });
+
+    let headers = [
+        (header::CONTENT_TYPE, "application/gzip".to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", file_name),
+        ),
+    ];
+
+    Ok((headers, Body::from_stream(ReaderStream::new(reader))))
+}
+
+#[derive(Deserialize)]
+struct ImportQuery {
+    name: String,
+    #[serde(default)]
+    conflict: NameConflict,
+}
+
+/// Unpacks a tar.gz from the body into a new project
+async fn post_import_project(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(container_service): State<Arc<dyn ContainerServiceTrait>>,
+    Query(query): Query<ImportQuery>,
+    body: Bytes,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.import(&query.name, query.conflict, &mut body.as_ref())?;
+
+    let json = project_details(&project_info, project_service, container_service)?;
+    Ok(Json(json).into_response())
+}
+
+#[derive(Deserialize)]
+struct CreateQuery {
+    template: Option<String>,
+}
+
+/// Creates an empty project, or renders a template with the variables of the body into it
 async fn post_create_project(
     State(project_service): State<Arc<dyn ProjectServiceTrait>>,
     State(container_service): State<Arc<dyn ContainerServiceTrait>>,
     Path(project_name): Path<String>,
+    Query(query): Query<CreateQuery>,
+    variables: Option<extract::Json<HashMap<String, String>>>,
 ) -> Result<impl IntoResponse, AppError> {
-    let project_info = project_service.create(&project_name)?;
+    let project_info = match query.template {
+        Some(template) => {
+            let variables = variables.map(|json| json.0).unwrap_or_default();
+            project_service.create_from_template(&project_name, &template, &variables)?
+        }
+        None => project_service.create(&project_name)?,
+    };
 
     let json = project_details(&project_info, project_service, container_service)?;
     Ok(Json(json).into_response())
 }
+
+#[derive(Deserialize)]
+struct NewProjectName {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct RenameQuery {
+    /// stop a running project and start it again under its new name
+    #[serde(default)]
+    restart: bool,
+}
+
+/// The compose project name derives from the directory, so a running project has to be
+/// stopped before its directory is renamed - otherwise its containers would be orphaned
+async fn post_rename_project(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(container_service): State<Arc<dyn ContainerServiceTrait>>,
+    Path(project_name): Path<String>,
+    Query(query): Query<RenameQuery>,
+    extract::Json(body): extract::Json<NewProjectName>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+
+    // reject an invalid or taken name before anything gets stopped
+    match project_service.project(&body.name) {
+        Ok(_) => return Err(ProjectServiceError::ProjectAlreadyExists(body.name).into()),
+        Err(ProjectServiceError::ProjectNotFound(_)) => {}
+        Err(error) => return Err(error.into()),
+    }
+
+    let is_online = container_service.is_online(&project_info)?;
+    if is_online && !query.restart {
+        return Err(ProjectServiceError::ProjectRunning(project_name).into());
+    }
+
+    if is_online {
+        container_service.stop(&project_info)?;
+    }
+
+    let renamed = match project_service.rename(&project_info, &body.name) {
+        Ok(renamed) => renamed,
+        Err(error) => {
+            if is_online {
+                let _ = container_service.start(&project_info);
+            }
+            return Err(error.into());
+        }
+    };
+
+    if is_online {
+        container_service.start(&renamed)?;
+    }
+
+    let json = project_details(&renamed, project_service, container_service)?;
+    Ok(Json(json).into_response())
+}
+
+async fn post_duplicate_project(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(container_service): State<Arc<dyn ContainerServiceTrait>>,
+    Path(project_name): Path<String>,
+    extract::Json(body): extract::Json<NewProjectName>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let duplicate = project_service.duplicate(&project_info, &body.name)?;
+
+    let json = project_details(&duplicate, project_service, container_service)?;
+    Ok(Json(json).into_response())
+}
+
+#[derive(Deserialize)]
+struct EnvQuery {
+    #[serde(default = "default_env_file")]
+    file: String,
+}
+
+fn default_env_file() -> String {
+    DEFAULT_ENV_FILE.to_string()
+}
+
+#[derive(Deserialize)]
+struct EnvUpdate {
+    entries: Vec<EnvEntry>,
+}
+
+async fn get_project_env(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+    Query(query): Query<EnvQuery>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let env = project_service.env(&project_info, &query.file)?;
+
+    Ok(Json(redact_env(project_service.as_ref(), &claims, env)))
+}
+
+async fn put_project_env(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+    Query(query): Query<EnvQuery>,
+    claims: Claims,
+    extract::Json(mut update): extract::Json<EnvUpdate>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+
+    if !claims.reveal_secrets {
+        let current = project_service.env(&project_info, &query.file)?.entries;
+        for entry in update
+            .entries
+            .iter_mut()
+            .filter(|entry| entry.value == MASK)
+        {
+            if let Some(original) = current.iter().find(|original| original.key == entry.key) {
+                entry.value = original.value.to_string();
+            }
+        }
+    }
+
+    let env = project_service.update_env(&project_info, &query.file, &update.entries)?;
+
+    Ok(Json(redact_env(project_service.as_ref(), &claims, env)))
+}
+
+fn redact_env(
+    project_service: &dyn ProjectServiceTrait,
+    claims: &Claims,
+    mut env: EnvFile,
+) -> EnvFile {
+    if !claims.reveal_secrets {
+        let policy = project_service.redaction_policy();
+        for entry in env
+            .entries
+            .iter_mut()
+            .filter(|entry| !entry.value.is_empty() && policy.is_sensitive(&entry.key))
+        {
+            entry.value = MASK.to_string();
+        }
+    }
+
+    env
+}
+
+async fn get_project_metadata(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let settings = project_service.settings(&project_info)?;
+
+    Ok(Json(settings.metadata))
+}
+
+async fn put_project_metadata(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+    extract::Json(metadata): extract::Json<ProjectMetadata>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let metadata = project_service.update_metadata(&project_info, metadata)?;
+
+    Ok(Json(metadata))
+}
+
+#[derive(Deserialize)]
+struct SecretUpdate {
+    value: String,
+}
+
+async fn get_project_secrets(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let secrets = project_service.secrets(&project_info)?;
+
+    Ok(Json(secrets))
+}
+
+async fn put_project_secret(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path((project_name, name)): Path<(String, String)>,
+    extract::Json(update): extract::Json<SecretUpdate>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let secret = project_service.set_secret(&project_info, &name, &update.value)?;
+
+    Ok(Json(secret))
+}
+
+async fn delete_project_secret(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path((project_name, name)): Path<(String, String)>,
+) -> Result<(), AppError> {
+    let project_info = project_service.project(&project_name)?;
+    project_service.delete_secret(&project_info, &name)?;
+
+    Ok(())
+}
+
+/// Masks the credentials of the channels unless the user may reveal them
+fn redact_notifications(
+    claims: &Claims,
+    notifications: NotificationSettings,
+) -> NotificationSettings {
+    if claims.reveal_secrets {
+        notifications
+    } else {
+        notifications.masked(MASK)
+    }
+}
+
+async fn get_project_notifications(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    claims: Claims,
+    Path(project_name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let settings = project_service.settings(&project_info)?;
+
+    Ok(Json(redact_notifications(&claims, settings.notifications)))
+}
+
+/// Credentials which are still masked keep their current value
+async fn put_project_notifications(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    claims: Claims,
+    Path(project_name): Path<String>,
+    extract::Json(notifications): extract::Json<NotificationSettings>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+
+    let settings = project_service.modify_settings(&project_info, &mut |settings| {
+        settings.notifications = notifications.clone().unmask(&settings.notifications, MASK);
+        Ok(())
+    })?;
+
+    Ok(Json(redact_notifications(&claims, settings.notifications)))
+}
+
+async fn get_project_schedules(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(schedule_service): State<Arc<dyn ScheduleServiceTrait>>,
+    Path(project_name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let schedules = schedule_service.schedules(&project_info)?;
+
+    Ok(Json(schedules))
+}
+
+async fn post_create_schedule(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(schedule_service): State<Arc<dyn ScheduleServiceTrait>>,
+    Path(project_name): Path<String>,
+    extract::Json(schedule): extract::Json<NewSchedule>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let schedule = schedule_service.add(&project_info, &schedule)?;
+
+    Ok(Json(schedule))
+}
+
+async fn put_update_schedule(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(schedule_service): State<Arc<dyn ScheduleServiceTrait>>,
+    Path((project_name, id)): Path<(String, u32)>,
+    extract::Json(schedule): extract::Json<NewSchedule>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let schedule = schedule_service.update(&project_info, id, &schedule)?;
+
+    Ok(Json(schedule))
+}
+
+async fn delete_schedule(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(schedule_service): State<Arc<dyn ScheduleServiceTrait>>,
+    Path((project_name, id)): Path<(String, u32)>,
+) -> Result<(), AppError> {
+    let project_info = project_service.project(&project_name)?;
+    schedule_service.remove(&project_info, id)?;
+
+    Ok(())
+}
+
+async fn get_project_updates(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(update_service): State<Arc<dyn UpdateServiceTrait>>,
+    Path(project_name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    let settings = project_service.settings(&project_info)?;
+
+    Ok(Json(json!({
+        "auto_update": settings.auto_update,
+        "services": update_service.updates(&project_info),
+    })))
+}
+
+#[derive(Deserialize)]
+struct UpdateSettings {
+    auto_update: bool,
+}
+
+async fn put_project_updates(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    State(update_service): State<Arc<dyn UpdateServiceTrait>>,
+    Path(project_name): Path<String>,
+    extract::Json(update): extract::Json<UpdateSettings>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+
+    let settings = project_service.modify_settings(&project_info, &mut |settings| {
+        settings.auto_update = update.auto_update;
+        Ok(())
+    })?;
+
+    Ok(Json(json!({
+        "auto_update": settings.auto_update,
+        "services": update_service.updates(&project_info),
+    })))
+}
+
+fn project_dependencies(
+    project_info: &ProjectInfo,
+    project_service: &Arc<dyn ProjectServiceTrait>,
+) -> Result<Value, AppError> {
+    let settings = project_service.settings(project_info)?;
+    let dependents = dependencies::dependents(project_service.as_ref(), project_info)?;
+
+    Ok(json!({
+        "dependencies": settings.dependencies,
+        "dependents": dependents.iter().map(|dependent| &dependent.name).collect::<Vec<_>>(),
+    }))
+}
+
+async fn get_project_dependencies(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+
+    Ok(Json(project_dependencies(&project_info, &project_service)?))
+}
+
+#[derive(Deserialize)]
+struct UpdateDependencies {
+    dependencies: Vec<String>,
+}
+
+async fn put_project_dependencies(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(project_name): Path<String>,
+    extract::Json(update): extract::Json<UpdateDependencies>,
+) -> Result<impl IntoResponse, AppError> {
+    let project_info = project_service.project(&project_name)?;
+    dependencies::validate_dependencies(
+        project_service.as_ref(),
+        &project_info,
+        &update.dependencies,
+    )?;
+
+    project_service.modify_settings(&project_info, &mut |settings| {
+        settings.dependencies = update.dependencies.clone();
+        Ok(())
+    })?;
+
+    Ok(Json(project_dependencies(&project_info, &project_service)?))
+}
@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router, extract::State, middleware::from_extractor_with_state, response::IntoResponse,
+    routing::get,
+};
+
+use crate::{AppError, AppState, services::project::ProjectServiceTrait};
+
+use super::auth::Claims;
+
+pub fn routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(get_roots))
+        .route("/skipped", get(get_skipped_dirs))
+        .route_layer(from_extractor_with_state::<Claims, _>(state.clone()))
+        .with_state(state)
+}
+
+async fn get_roots(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+) -> impl IntoResponse {
+    Json(project_service.roots())
+}
+
+/// Dirs of the roots which are not listed as projects and why
+async fn get_skipped_dirs(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+) -> Result<impl IntoResponse, AppError> {
+    let discovery = project_service.discover()?;
+    Ok(Json(discovery.skipped))
+}
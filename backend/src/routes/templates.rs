@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router, extract::State, middleware::from_extractor_with_state, response::IntoResponse,
+    routing::get,
+};
+
+use crate::{AppError, AppState, services::project::ProjectServiceTrait};
+
+use super::auth::Claims;
+
+pub fn routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(get_templates))
+        .route_layer(from_extractor_with_state::<Claims, _>(state.clone()))
+        .with_state(state)
+}
+
+async fn get_templates(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+) -> Result<impl IntoResponse, AppError> {
+    let templates = project_service.templates()?;
+    Ok(Json(templates))
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    middleware::from_extractor_with_state,
+    response::IntoResponse,
+    routing::{delete, get, post},
+};
+
+use crate::{AppError, AppState, services::project::ProjectServiceTrait};
+
+use super::auth::Claims;
+
+pub fn routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(get_trash))
+        .route("/", delete(delete_trash))
+        .route("/{id}", delete(delete_trash_entry))
+        .route("/{id}/restore", post(post_restore_trash_entry))
+        .route_layer(from_extractor_with_state::<Claims, _>(state.clone()))
+        .with_state(state)
+}
+
+async fn get_trash(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+) -> Result<impl IntoResponse, AppError> {
+    let entries = project_service.trash()?;
+    Ok(Json(entries))
+}
+
+async fn post_restore_trash_entry(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let entry = project_service.restore(&id)?;
+    Ok(Json(entry))
+}
+
+async fn delete_trash_entry(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let entry = project_service.purge(&id)?;
+    Ok(Json(entry))
+}
+
+/// Empties the whole trash
+async fn delete_trash(
+    State(project_service): State<Arc<dyn ProjectServiceTrait>>,
+) -> Result<impl IntoResponse, AppError> {
+    let purged = project_service
+        .trash()?
+        .iter()
+        .map(|entry| project_service.purge(&entry.id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Json(purged))
+}
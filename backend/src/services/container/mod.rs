@@ -1,39 +1,129 @@
 use axum::{Json, http::StatusCode, response::IntoResponse, response::Response};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error;
 
 use super::project::ProjectInfo;
 
+pub mod reconciler;
+pub mod secrets;
 pub mod service;
 
 pub type Result<T> = core::result::Result<T, Error>;
 pub type Error = ContainerServiceError;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq)]
 pub enum ContainerServiceError {
     #[error("Could not find Project {0}")]
     NotFound(String),
 
     #[error("Failed to exec command '{command}' - {error}")]
     FailedToExecCommand { error: String, command: String },
+
+    #[error("Failed to provide the secrets of {project} - {error}")]
+    FailedToProvideSecrets { project: String, error: String },
 }
 
 impl IntoResponse for ContainerServiceError {
     fn into_response(self) -> Response {
-        let status = match &self {
-            ContainerServiceError::NotFound(_) => StatusCode::NOT_FOUND,
-            ContainerServiceError::FailedToExecCommand { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        let (status, code) = match &self {
+            ContainerServiceError::NotFound(_) => (StatusCode::NOT_FOUND, "container_not_found"),
+            ContainerServiceError::FailedToExecCommand { .. } => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "command_failed")
+            }
+            ContainerServiceError::FailedToProvideSecrets { .. } => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "provide_secrets_failed")
+            }
         };
 
-        let body = Json(json!({ "error": self.to_string() }));
+        let body = Json(json!({ "error": self.to_string(), "code": code }));
         (status, body).into_response()
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ServiceInfo {
+    pub name: String,
+    pub image: String,
+    pub state: String,
+    pub exit_code: i32,
+}
+
+impl ServiceInfo {
+    /// A service counts as crashed if it is stuck restarting or exited on its own with an error
+    pub fn is_crashed(&self) -> bool {
+        match self.state.as_str() {
+            "restarting" | "dead" => true,
+            "exited" => self.exit_code != 0,
+            _ => false,
+        }
+    }
+}
+
+/// How much of a project is up, ordered from fully running to stopped
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectStatus {
+    Running,
+    /// some services run while others are stopped or exited
+    Partial,
+    Stopped,
+}
+
+impl ProjectStatus {
+    pub fn is_online(&self) -> bool {
+        *self != ProjectStatus::Stopped
+    }
+
+    /// Derives the status from the services of a project which is online
+    pub fn of_services(services: &[ServiceInfo]) -> ProjectStatus {
+        if services.iter().all(|service| service.state == "running") {
+            ProjectStatus::Running
+        } else {
+            ProjectStatus::Partial
+        }
+    }
+}
+
+/// Which images `docker compose down` removes together with the containers
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoveImages {
+    /// every image used by a service
+    All,
+    /// only images without a custom tag
+    Local,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct DownOptions {
+    /// also remove named volumes, the data in them is lost
+    #[serde(default)]
+    pub volumes: bool,
+    #[serde(default)]
+    pub rmi: Option<RemoveImages>,
+}
+
 pub trait ContainerServiceTrait: Send + Sync {
     fn are_online(&self, projects: &[ProjectInfo]) -> Result<Vec<bool>>;
     fn is_online(&self, project: &ProjectInfo) -> Result<bool>;
+    /// Like `are_online`, but tells projects with only some running services apart
+    fn statuses(&self, projects: &[ProjectInfo]) -> Result<Vec<ProjectStatus>> {
+        self.are_online(projects)?
+            .into_iter()
+            .zip(projects)
+            .map(|(is_online, project)| match is_online {
+                true => Ok(ProjectStatus::of_services(&self.services(project)?)),
+                false => Ok(ProjectStatus::Stopped),
+            })
+            .collect()
+    }
+    fn services(&self, project: &ProjectInfo) -> Result<Vec<ServiceInfo>>;
+    /// Digests (`repository@sha256:...`) the local image was pulled with
+    fn image_digests(&self, image: &str) -> Result<Vec<String>>;
     fn stop(&self, project: &ProjectInfo) -> Result<()>;
+    /// Removes containers and networks of the project, volumes and images only if requested
+    fn down(&self, project: &ProjectInfo, options: &DownOptions) -> Result<()>;
     fn start(&self, project: &ProjectInfo) -> Result<()>;
     fn pull(&self, project: &ProjectInfo) -> Result<()>;
 }
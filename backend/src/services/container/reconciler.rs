@@ -0,0 +1,108 @@
+use std::{sync::Arc, time::Duration};
+
+use itertools::Itertools;
+use tracing::{error, info};
+
+use crate::services::{
+    notification::{NotificationEvent, NotificationServiceTrait},
+    project::{DesiredState, ProjectServiceTrait, dependencies},
+};
+
+use super::ContainerServiceTrait;
+
+/// Starts or stops projects whose actual state drifted from their desired state,
+/// e.g. projects without `restart: always` after a reboot of the host
+pub struct Reconciler {
+    project_service: Arc<dyn ProjectServiceTrait>,
+    container_service: Arc<dyn ContainerServiceTrait>,
+    notification_service: Arc<dyn NotificationServiceTrait>,
+}
+
+impl Reconciler {
+    pub fn new(
+        project_service: Arc<dyn ProjectServiceTrait>,
+        container_service: Arc<dyn ContainerServiceTrait>,
+        notification_service: Arc<dyn NotificationServiceTrait>,
+    ) -> Reconciler {
+        Self {
+            project_service,
+            container_service,
+            notification_service,
+        }
+    }
+
+    pub fn reconcile(&self) {
+        let projects = match self.project_service.all_projects() {
+            Ok(projects) => projects,
+            Err(err) => {
+                error!("{}", err);
+                return;
+            }
+        };
+
+        // dependencies are started first and stopped last
+        let projects = match dependencies::start_order_skipping_missing(
+            self.project_service.as_ref(),
+            &projects,
+        ) {
+            Ok(ordered) => ordered,
+            Err(err) => {
+                error!("{}", err);
+                projects
+            }
+        };
+
+        let managed = projects
+            .into_iter()
+            .filter_map(|project| match self.project_service.settings(&project) {
+                Ok(settings) => settings.desired_state.map(|state| (project, state)),
+                Err(err) => {
+                    error!("{}", err);
+                    None
+                }
+            })
+            .collect_vec();
+
+        let (projects, desired): (Vec<_>, Vec<_>) = managed.into_iter().unzip();
+
+        // failures are already logged by the container service
+        let Ok(are_online) = self.container_service.are_online(&projects) else {
+            return;
+        };
+
+        let (to_start, to_stop): (Vec<_>, Vec<_>) = projects
+            .iter()
+            .zip(desired)
+            .zip(are_online)
+            .filter(|((_, desired), is_online)| (*desired == DesiredState::Running) != *is_online)
+            .map(|((project, desired), _)| (project, desired))
+            .partition(|(_, desired)| *desired == DesiredState::Running);
+
+        // dependents are stopped before the projects they depend on
+        for (project, _) in to_stop.into_iter().rev() {
+            info!("stopping {} to match its desired state", project.name);
+
+            let result = self.container_service.stop(project);
+            self.notification_service
+                .notify_outcome(project, NotificationEvent::Stopped, &result);
+        }
+
+        for (project, _) in to_start {
+            info!("starting {} to match its desired state", project.name);
+
+            let result = self.container_service.start(project);
+            self.notification_service
+                .notify_outcome(project, NotificationEvent::Started, &result);
+        }
+    }
+
+    /// Reconciles right away and then every `interval` until the task is dropped
+    pub async fn run(self, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+            tokio::task::block_in_place(|| self.reconcile());
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use tracing::warn;
+
+use super::{
+    ContainerServiceError, ContainerServiceTrait, DownOptions, ProjectInfo, ProjectStatus, Result,
+    ServiceInfo,
+};
+use crate::services::project::{ProjectServiceTrait, secrets::SECRETS_DIR};
+
+/// Wraps a container service, so the decrypted secrets of a project only exist on disk while
+/// it runs. Every start writes them right before compose reads them, stop and down remove them.
+/// Other commands which load the compose file get them written for as long as they run
+pub struct SecretsContainerService {
+    inner: Arc<dyn ContainerServiceTrait>,
+    project_service: Arc<dyn ProjectServiceTrait>,
+}
+
+impl SecretsContainerService {
+    pub fn new(
+        inner: Arc<dyn ContainerServiceTrait>,
+        project_service: Arc<dyn ProjectServiceTrait>,
+    ) -> SecretsContainerService {
+        SecretsContainerService {
+            inner,
+            project_service,
+        }
+    }
+
+    /// Compose loads the whole project for every command, so a stopped project needs its
+    /// secrets as well, e.g. to be pulled. They are removed again unless they existed before
+    fn with_secrets<T>(
+        &self,
+        project: &ProjectInfo,
+        command: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let existed = project.dir.join(SECRETS_DIR).exists();
+
+        if !existed && let Err(err) = self.project_service.materialize_secrets(project) {
+            // the command may not need them, e.g. a down after the key was changed
+            warn!(
+                "failed to provide the secrets of {} - {}",
+                project.name, err
+            );
+        }
+
+        let result = command();
+
+        if !existed {
+            self.clear_secrets(project);
+        }
+        result
+    }
+
+    fn clear_secrets(&self, project: &ProjectInfo) {
+        if let Err(err) = self.project_service.clear_secrets(project) {
+            warn!("failed to remove the secrets of {} - {}", project.name, err);
+        }
+    }
+}
+
+impl ContainerServiceTrait for SecretsContainerService {
+    fn are_online(&self, projects: &[ProjectInfo]) -> Result<Vec<bool>> {
+        self.inner.are_online(projects)
+    }
+
+    fn is_online(&self, project: &ProjectInfo) -> Result<bool> {
+        self.inner.is_online(project)
+    }
+
+    fn statuses(&self, projects: &[ProjectInfo]) -> Result<Vec<ProjectStatus>> {
+        self.inner.statuses(projects)
+    }
+
+    fn services(&self, project: &ProjectInfo) -> Result<Vec<ServiceInfo>> {
+        self.with_secrets(project, || self.inner.services(project))
+    }
+
+    fn image_digests(&self, image: &str) -> Result<Vec<String>> {
+        self.inner.image_digests(image)
+    }
+
+    fn stop(&self, project: &ProjectInfo) -> Result<()> {
+        self.with_secrets(project, || self.inner.stop(project))?;
+        self.clear_secrets(project);
+        Ok(())
+    }
+
+    fn down(&self, project: &ProjectInfo, options: &DownOptions) -> Result<()> {
+        self.with_secrets(project, || self.inner.down(project, options))?;
+        self.clear_secrets(project);
+        Ok(())
+    }
+
+    fn start(&self, project: &ProjectInfo) -> Result<()> {
+        self.project_service
+            .materialize_secrets(project)
+            .map_err(|err| ContainerServiceError::FailedToProvideSecrets {
+                project: project.name.to_string(),
+                error: err.to_string(),
+            })?;
+
+        self.inner.start(project)
+    }
+
+    fn pull(&self, project: &ProjectInfo) -> Result<()> {
+        self.with_secrets(project, || self.inner.pull(project))
+    }
+}
@@ -4,17 +4,49 @@ use std::{
 };
 
 use itertools::Itertools;
+use serde::Deserialize;
 use tracing::warn;
 
 use crate::services::container::ContainerServiceError;
 
-use super::{ContainerServiceTrait, ProjectInfo};
+use super::{
+    ContainerServiceTrait, DownOptions, ProjectInfo, ProjectStatus, RemoveImages, ServiceInfo,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ComposeLsEntry {
+    name: String,
+    /// container states with their count, e.g. `running(2), exited(1)`
+    status: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ComposePsEntry {
+    service: String,
+    image: String,
+    state: String,
+    #[serde(default)]
+    exit_code: i32,
+}
+
+impl From<ComposePsEntry> for ServiceInfo {
+    fn from(entry: ComposePsEntry) -> Self {
+        ServiceInfo {
+            name: entry.service,
+            image: entry.image,
+            state: entry.state,
+            exit_code: entry.exit_code,
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct ContainerService;
 
 impl ContainerService {
-    fn exec_docker_compose_command(
+    fn exec_docker_command(
         &self,
         base_dir: Option<&PathBuf>,
         args: &[&str],
@@ -25,7 +57,7 @@ impl ContainerService {
             command.current_dir(path);
         };
 
-        let output = command.arg("compose").args(args).output().map_err(|err| {
+        let output = command.args(args).output().map_err(|err| {
             let error = ContainerServiceError::FailedToExecCommand {
                 command: args.join(" ").to_string(),
                 error: err.to_string(),
@@ -47,6 +79,46 @@ impl ContainerService {
 
         Ok(output)
     }
+
+    fn exec_docker_compose_command(
+        &self,
+        base_dir: Option<&PathBuf>,
+        args: &[&str],
+    ) -> super::Result<Output> {
+        self.exec_docker_command(base_dir, &[&["compose"], args].concat())
+    }
+
+    /// Runs a compose command inside the project dir. Projects outside the default root get
+    /// their name passed explicitly, docker compose would only use the dir name
+    fn exec_project_command(&self, project: &ProjectInfo, args: &[&str]) -> super::Result<Output> {
+        let name = project.compose_name();
+        let mut project_args = Vec::new();
+        if project.root().is_some() {
+            project_args.extend(["-p", name.as_str()]);
+        }
+
+        self.exec_docker_compose_command(Some(&project.dir), &[&project_args, args].concat())
+    }
+
+    /// `docker compose ps --format json` prints an array on older versions and
+    /// one object per line on newer ones
+    fn parse_ps_output(output: &str) -> super::Result<Vec<ComposePsEntry>> {
+        let to_error = |err: serde_json::Error| ContainerServiceError::FailedToExecCommand {
+            command: "ps -a --format json".to_string(),
+            error: err.to_string(),
+        };
+
+        let output = output.trim();
+        if output.starts_with('[') {
+            return serde_json::from_str(output).map_err(to_error);
+        }
+
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(to_error))
+            .collect()
+    }
 }
 
 impl ContainerServiceTrait for ContainerService {
@@ -60,7 +132,7 @@ impl ContainerServiceTrait for ContainerService {
 
         let active = projects
             .iter()
-            .map(|project_info| active_projects.contains(&project_info.name))
+            .map(|project_info| active_projects.contains(&project_info.compose_name()))
             .collect_vec();
 
         Ok(active)
@@ -70,18 +142,105 @@ impl ContainerServiceTrait for ContainerService {
         Ok(*self.are_online(&[(*project).clone()])?.first().unwrap())
     }
 
+    /// Reads the states of all projects with a single `compose ls` instead of one `ps` per project
+    fn statuses(&self, projects: &[ProjectInfo]) -> super::Result<Vec<ProjectStatus>> {
+        let output = self.exec_docker_compose_command(None, &["ls", "-a", "--format", "json"])?;
+
+        let entries: Vec<ComposeLsEntry> =
+            serde_json::from_slice(&output.stdout).map_err(|err| {
+                ContainerServiceError::FailedToExecCommand {
+                    command: "ls -a --format json".to_string(),
+                    error: err.to_string(),
+                }
+            })?;
+
+        let statuses = projects
+            .iter()
+            .map(|project| {
+                let Some(entry) = entries
+                    .iter()
+                    .find(|entry| entry.name == project.compose_name())
+                else {
+                    return ProjectStatus::Stopped;
+                };
+
+                let states = entry
+                    .status
+                    .split(',')
+                    .map(|state| state.trim().split('(').next().unwrap_or_default())
+                    .collect_vec();
+
+                match (states.contains(&"running"), states.len()) {
+                    (false, _) => ProjectStatus::Stopped,
+                    (true, 1) => ProjectStatus::Running,
+                    (true, _) => ProjectStatus::Partial,
+                }
+            })
+            .collect();
+
+        Ok(statuses)
+    }
+
+    fn services(&self, project: &ProjectInfo) -> super::Result<Vec<ServiceInfo>> {
+        let output = self.exec_project_command(project, &["ps", "-a", "--format", "json"])?;
+
+        let services = Self::parse_ps_output(&String::from_utf8_lossy(&output.stdout))?
+            .into_iter()
+            .map(ServiceInfo::from)
+            .collect();
+
+        Ok(services)
+    }
+
+    fn image_digests(&self, image: &str) -> super::Result<Vec<String>> {
+        let output = self.exec_docker_command(
+            None,
+            &[
+                "image",
+                "inspect",
+                "--format",
+                "{{json .RepoDigests}}",
+                image,
+            ],
+        )?;
+
+        serde_json::from_slice(&output.stdout).map_err(|err| {
+            ContainerServiceError::FailedToExecCommand {
+                command: format!("image inspect {}", image),
+                error: err.to_string(),
+            }
+        })
+    }
+
     fn stop(&self, project: &ProjectInfo) -> super::Result<()> {
-        self.exec_docker_compose_command(Some(&project.dir), &["down"])?;
+        self.exec_project_command(project, &["down"])?;
+        Ok(())
+    }
+
+    fn down(&self, project: &ProjectInfo, options: &DownOptions) -> super::Result<()> {
+        let mut args = vec!["down", "--remove-orphans"];
+
+        if options.volumes {
+            args.push("--volumes");
+        }
+
+        match options.rmi {
+            Some(RemoveImages::All) => args.extend(["--rmi", "all"]),
+            Some(RemoveImages::Local) => args.extend(["--rmi", "local"]),
+            None => {}
+        }
+
+        self.exec_project_command(project, &args)?;
         Ok(())
     }
 
     fn start(&self, project: &ProjectInfo) -> super::Result<()> {
-        self.exec_docker_compose_command(Some(&project.dir), &["up", "-d"])?;
+        self.exec_project_command(project, &["up", "-d"])?;
         Ok(())
     }
 
     fn pull(&self, project: &ProjectInfo) -> super::Result<()> {
-        self.exec_docker_compose_command(Some(&project.dir), &["pull"])?;
+        self.exec_project_command(project, &["pull"])?;
         Ok(())
     }
 }
@@ -1,2 +1,5 @@
 pub mod container;
+pub mod notification;
 pub mod project;
+pub mod schedule;
+pub mod update;
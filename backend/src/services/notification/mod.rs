@@ -0,0 +1,197 @@
+use std::{
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{container::ContainerServiceError, project::ProjectInfo};
+
+pub mod service;
+pub mod watcher;
+
+pub type Result<T> = core::result::Result<T, Error>;
+pub type Error = NotificationServiceError;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum NotificationServiceError {
+    #[error("Failed to deliver notification via {channel} - {error}")]
+    DeliveryFailed { channel: String, error: String },
+
+    #[error("Email delivery is not configured")]
+    EmailNotConfigured,
+
+    #[error("Invalid email address {0}")]
+    InvalidAddress(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    Started,
+    Stopped,
+    CommandFailed,
+    Crashed,
+}
+
+impl Display for NotificationEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            NotificationEvent::Started => "started",
+            NotificationEvent::Stopped => "stopped",
+            NotificationEvent::CommandFailed => "command failed",
+            NotificationEvent::Crashed => "crashed",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Webhook { url: String },
+    Email { to: Vec<String> },
+    Ntfy { url: String, token: Option<String> },
+    Gotify { url: String, token: String },
+}
+
+impl NotificationChannel {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NotificationChannel::Webhook { .. } => "webhook",
+            NotificationChannel::Email { .. } => "email",
+            NotificationChannel::Ntfy { .. } => "ntfy",
+            NotificationChannel::Gotify { .. } => "gotify",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub channels: Vec<NotificationChannel>,
+    /// events to notify about - an empty list means all events
+    pub events: Vec<NotificationEvent>,
+}
+
+impl NotificationSettings {
+    pub fn wants(&self, event: NotificationEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+
+    /// Replaces the credentials of all channels with `mask` - tokens as well as webhook URLs,
+    /// which usually embed one
+    pub fn masked(&self, mask: &str) -> NotificationSettings {
+        let channels = self
+            .channels
+            .iter()
+            .map(|channel| match channel {
+                NotificationChannel::Webhook { .. } => NotificationChannel::Webhook {
+                    url: mask.to_string(),
+                },
+                NotificationChannel::Ntfy { url, token } => NotificationChannel::Ntfy {
+                    url: url.to_string(),
+                    token: token.as_ref().map(|_| mask.to_string()),
+                },
+                NotificationChannel::Gotify { url, .. } => NotificationChannel::Gotify {
+                    url: url.to_string(),
+                    token: mask.to_string(),
+                },
+                channel => channel.clone(),
+            })
+            .collect();
+
+        NotificationSettings {
+            channels,
+            events: self.events.clone(),
+        }
+    }
+
+    /// Puts the credentials of `original` back where a channel still holds `mask`, channels
+    /// are matched by their position and type
+    pub fn unmask(mut self, original: &NotificationSettings, mask: &str) -> NotificationSettings {
+        for (channel, original) in self.channels.iter_mut().zip(&original.channels) {
+            match (channel, original) {
+                (
+                    NotificationChannel::Webhook { url },
+                    NotificationChannel::Webhook { url: original },
+                ) if url == mask => *url = original.to_string(),
+                (
+                    NotificationChannel::Ntfy { token, .. },
+                    NotificationChannel::Ntfy {
+                        token: original, ..
+                    },
+                ) if token.as_deref() == Some(mask) => *token = original.clone(),
+                (
+                    NotificationChannel::Gotify { token, .. },
+                    NotificationChannel::Gotify {
+                        token: original, ..
+                    },
+                ) if token == mask => *token = original.to_string(),
+                _ => {}
+            }
+        }
+
+        self
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct Notification {
+    pub project: String,
+    pub event: NotificationEvent,
+    pub message: String,
+    pub timestamp: u64,
+}
+
+impl Notification {
+    pub fn new(project: &ProjectInfo, event: NotificationEvent, message: &str) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        Self {
+            project: project.name.to_string(),
+            event,
+            message: message.to_string(),
+            timestamp,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        format!("{} {}", self.project, self.event)
+    }
+}
+
+pub trait NotificationServiceTrait: Send + Sync {
+    /// Sends the event to all channels configured for the project without waiting for delivery
+    fn notify(&self, project: &ProjectInfo, event: NotificationEvent, message: &str);
+
+    /// Sends `event` if the container command succeeded and [`NotificationEvent::CommandFailed`] if it failed
+    fn notify_outcome(
+        &self,
+        project: &ProjectInfo,
+        event: NotificationEvent,
+        result: &core::result::Result<(), ContainerServiceError>,
+    ) {
+        match result {
+            Ok(()) => self.notify(
+                project,
+                event,
+                &format!("Project {} {}", project.name, event),
+            ),
+            Err(
+                error @ (ContainerServiceError::FailedToExecCommand { .. }
+                | ContainerServiceError::FailedToProvideSecrets { .. }),
+            ) => self.notify(
+                project,
+                NotificationEvent::CommandFailed,
+                &error.to_string(),
+            ),
+            Err(_) => {}
+        }
+    }
+}
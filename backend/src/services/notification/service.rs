@@ -0,0 +1,193 @@
+use std::sync::Arc;
+
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor, message::Mailbox,
+    transport::smtp::authentication::Credentials,
+};
+use tracing::{error, warn};
+
+use crate::services::project::{ProjectInfo, ProjectServiceTrait};
+
+use super::{
+    Notification, NotificationChannel, NotificationEvent, NotificationServiceError,
+    NotificationServiceTrait,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmtpTls {
+    None,
+    StartTls,
+    Tls,
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub credentials: Option<(String, String)>,
+    pub from: String,
+}
+
+#[derive(Clone)]
+pub struct NotificationService {
+    project_service: Arc<dyn ProjectServiceTrait>,
+    smtp: Option<Arc<SmtpConfig>>,
+    client: reqwest::Client,
+}
+
+impl NotificationService {
+    pub fn new(
+        project_service: Arc<dyn ProjectServiceTrait>,
+        smtp: Option<SmtpConfig>,
+    ) -> NotificationService {
+        Self {
+            project_service,
+            smtp: smtp.map(Arc::new),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn deliver(
+        &self,
+        channel: &NotificationChannel,
+        notification: &Notification,
+    ) -> super::Result<()> {
+        let to_error = |err: String| NotificationServiceError::DeliveryFailed {
+            channel: channel.kind().to_string(),
+            error: err,
+        };
+
+        match channel {
+            NotificationChannel::Webhook { url } => {
+                self.client
+                    .post(url)
+                    .json(notification)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|err| to_error(err.to_string()))?;
+            }
+            NotificationChannel::Ntfy { url, token } => {
+                let mut request = self
+                    .client
+                    .post(url)
+                    .header("Title", notification.title())
+                    .body(notification.message.clone());
+
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+
+                request
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|err| to_error(err.to_string()))?;
+            }
+            NotificationChannel::Gotify { url, token } => {
+                self.client
+                    .post(format!("{}/message", url.trim_end_matches('/')))
+                    .header("X-Gotify-Key", token)
+                    .json(&serde_json::json!({
+                        "title": notification.title(),
+                        "message": notification.message,
+                    }))
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|err| to_error(err.to_string()))?;
+            }
+            NotificationChannel::Email { to } => {
+                self.send_email(to, notification).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn send_email(&self, to: &[String], notification: &Notification) -> super::Result<()> {
+        let smtp = self
+            .smtp
+            .as_ref()
+            .ok_or(NotificationServiceError::EmailNotConfigured)?;
+
+        let parse_mailbox = |address: &str| {
+            address
+                .parse::<Mailbox>()
+                .map_err(|_| NotificationServiceError::InvalidAddress(address.to_string()))
+        };
+
+        let mut message = Message::builder()
+            .from(parse_mailbox(&smtp.from)?)
+            .subject(format!("[ContainerYard] {}", notification.title()));
+
+        for address in to {
+            message = message.to(parse_mailbox(address)?);
+        }
+
+        let to_error = |err: String| NotificationServiceError::DeliveryFailed {
+            channel: "email".to_string(),
+            error: err,
+        };
+
+        let message = message
+            .body(notification.message.clone())
+            .map_err(|err| to_error(err.to_string()))?;
+
+        let mut transport = match smtp.tls {
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host),
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)
+                .map_err(|err| to_error(err.to_string()))?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host)
+                .map_err(|err| to_error(err.to_string()))?,
+        }
+        .port(smtp.port);
+
+        if let Some((user, password)) = &smtp.credentials {
+            transport = transport.credentials(Credentials::new(user.clone(), password.clone()));
+        }
+
+        transport
+            .build()
+            .send(message)
+            .await
+            .map_err(|err| to_error(err.to_string()))?;
+
+        Ok(())
+    }
+}
+
+impl NotificationServiceTrait for NotificationService {
+    fn notify(&self, project: &ProjectInfo, event: NotificationEvent, message: &str) {
+        let settings = match self.project_service.settings(project) {
+            Ok(settings) => settings.notifications,
+            Err(err) => {
+                error!("{}", err);
+                return;
+            }
+        };
+
+        if !settings.wants(event) || settings.channels.is_empty() {
+            return;
+        }
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!("no runtime to deliver notifications for {}", project.name);
+            return;
+        };
+
+        let notification = Notification::new(project, event, message);
+
+        for channel in settings.channels {
+            let service = self.clone();
+            let notification = notification.clone();
+
+            runtime.spawn(async move {
+                if let Err(err) = service.deliver(&channel, &notification).await {
+                    warn!("{}", err);
+                }
+            });
+        }
+    }
+}
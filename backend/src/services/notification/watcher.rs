@@ -0,0 +1,78 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use tracing::error;
+
+use crate::services::{container::ContainerServiceTrait, project::ProjectServiceTrait};
+
+use super::{NotificationEvent, NotificationServiceTrait};
+
+/// Periodically inspects the services of all projects and sends a
+/// [`NotificationEvent::Crashed`] once for every service that crashed
+pub struct CrashWatcher {
+    project_service: Arc<dyn ProjectServiceTrait>,
+    container_service: Arc<dyn ContainerServiceTrait>,
+    notification_service: Arc<dyn NotificationServiceTrait>,
+    reported: HashSet<(String, String)>,
+}
+
+impl CrashWatcher {
+    pub fn new(
+        project_service: Arc<dyn ProjectServiceTrait>,
+        container_service: Arc<dyn ContainerServiceTrait>,
+        notification_service: Arc<dyn NotificationServiceTrait>,
+    ) -> CrashWatcher {
+        Self {
+            project_service,
+            container_service,
+            notification_service,
+            reported: HashSet::new(),
+        }
+    }
+
+    pub fn check(&mut self) {
+        let projects = match self.project_service.all_projects() {
+            Ok(projects) => projects,
+            Err(err) => {
+                error!("{}", err);
+                return;
+            }
+        };
+
+        let mut crashed = HashSet::new();
+
+        for project in projects {
+            // failures are already logged by the container service
+            let Ok(services) = self.container_service.services(&project) else {
+                continue;
+            };
+
+            for service in services.into_iter().filter(|service| service.is_crashed()) {
+                let key = (project.name.clone(), service.name.clone());
+
+                if !self.reported.contains(&key) {
+                    self.notification_service.notify(
+                        &project,
+                        NotificationEvent::Crashed,
+                        &format!(
+                            "Service {} of Project {} crashed ({}, exit code {})",
+                            service.name, project.name, service.state, service.exit_code
+                        ),
+                    );
+                }
+
+                crashed.insert(key);
+            }
+        }
+
+        self.reported = crashed;
+    }
+
+    pub async fn run(mut self, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+            tokio::task::block_in_place(|| self.check());
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::{
+    fs,
+    io::{self, ErrorKind, Read, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use itertools::Itertools;
+use tar::{Archive, Builder, EntryType};
+
+use super::{
+    ProjectServiceError, Result, SETTINGS_FILE, is_env_file, secrets::SECRETS_DIR,
+    service::io_error,
+};
+
+/// An archive may hold at most this many entries
+pub const MAX_ENTRIES: usize = 10_000;
+
+/// All files of an archive together may be this many times as large as a single file
+pub const MAX_UNPACKED_RATIO: u64 = 4;
+
+/// Writes the content of `dir` as tar.gz, paths in the archive are relative to `dir`.
+/// `exclude_env` also leaves out the settings, as they hold the credentials of notification channels
+pub fn export(dir: &Path, exclude_env: bool, writer: &mut dyn Write) -> Result<()> {
+    let failed = |err: io::Error| io_error(dir, err, ProjectServiceError::FailedToReadFile);
+
+    let mut builder = Builder::new(GzEncoder::new(writer, Compression::default()));
+    // keep symlinks as links instead of packing the files they point to
+    builder.follow_symlinks(false);
+
+    append_dir(&mut builder, dir, Path::new(""), exclude_env).map_err(failed)?;
+    builder
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(failed)?;
+
+    Ok(())
+}
+
+fn append_dir<W: Write>(
+    builder: &mut Builder<W>,
+    dir: &Path,
+    prefix: &Path,
+    exclude_env: bool,
+) -> io::Result<()> {
+    let entries = fs::read_dir(dir)?
+        .collect::<io::Result<Vec<_>>>()?
+        .into_iter()
+        .sorted_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name();
+        let path = entry.path();
+        let archive_path = prefix.join(&name);
+
+        if exclude_env && is_env_file(&name.to_string_lossy()) {
+            continue;
+        }
+
+        // decrypted secrets never leave the server, the encrypted ones are part of the settings
+        if archive_path == Path::new(SECRETS_DIR) {
+            continue;
+        }
+
+        if exclude_env && archive_path == Path::new(SETTINGS_FILE) {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            builder.append_dir(&archive_path, &path)?;
+            append_dir(builder, &path, &archive_path, exclude_env)?;
+        } else {
+            builder.append_path_with_name(&path, &archive_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Unpacks a tar.gz into the existing `dir`. Entries and symlinks leaving the dir, hard links
+/// and device files are rejected, so a crafted archive can't write anywhere else.
+/// Too many entries or too much content in total are rejected as well, so it can't fill the disk
+pub fn unpack(archive: &mut dyn Read, dir: &Path, max_file_size: usize) -> Result<()> {
+    let invalid = |err: io::Error| match err.kind() {
+        ErrorKind::InvalidData
+        | ErrorKind::InvalidInput
+        | ErrorKind::UnexpectedEof
+        | ErrorKind::Other => ProjectServiceError::InvalidArchive(err.to_string()),
+        _ => io_error(dir, err, ProjectServiceError::FailedToWriteFile),
+    };
+
+    let mut archive = Archive::new(GzDecoder::new(archive));
+    let max_total_size = (max_file_size as u64).saturating_mul(MAX_UNPACKED_RATIO);
+    let mut total_size = 0u64;
+
+    for (index, entry) in archive.entries().map_err(invalid)?.enumerate() {
+        if index >= MAX_ENTRIES {
+            return Err(ProjectServiceError::InvalidArchive(format!(
+                "more than {} entries",
+                MAX_ENTRIES
+            )));
+        }
+
+        let mut entry = entry.map_err(invalid)?;
+        let path = entry.path().map_err(invalid)?.into_owned();
+
+        let Some(relative) = inside_path(&path) else {
+            return Err(ProjectServiceError::InvalidArchive(format!(
+                "{:?} leaves the project directory",
+                path
+            )));
+        };
+
+        match entry.header().entry_type() {
+            EntryType::Regular | EntryType::Directory => {}
+            EntryType::Symlink => {
+                let target = entry.link_name().map_err(invalid)?.unwrap_or_default();
+                if !link_stays_inside(&relative, &target) {
+                    return Err(ProjectServiceError::InvalidArchive(format!(
+                        "symlink {:?} points outside of the project directory",
+                        path
+                    )));
+                }
+            }
+            // metadata of the archive itself
+            EntryType::XGlobalHeader => continue,
+            other => {
+                return Err(ProjectServiceError::InvalidArchive(format!(
+                    "{:?} has the unsupported type {:?}",
+                    path, other
+                )));
+            }
+        }
+
+        if relative.as_os_str().is_empty() || relative.starts_with(SECRETS_DIR) {
+            continue;
+        }
+
+        if entry.size() > max_file_size as u64 {
+            return Err(ProjectServiceError::FileTooLarge {
+                file: relative.to_string_lossy().to_string(),
+                max_size: max_file_size,
+            });
+        }
+
+        total_size += entry.size();
+        if total_size > max_total_size {
+            return Err(ProjectServiceError::InvalidArchive(format!(
+                "unpacks to more than {} bytes",
+                max_total_size
+            )));
+        }
+
+        entry.unpack_in(dir).map_err(invalid)?;
+    }
+
+    Ok(())
+}
+
+/// The path without `.` components, `None` if it is absolute or contains `..`
+fn inside_path(path: &Path) -> Option<PathBuf> {
+    path.components()
+        .filter(|component| *component != Component::CurDir)
+        .map(|component| match component {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Resolves the link target next to the link without touching the file system
+fn link_stays_inside(link: &Path, target: &Path) -> bool {
+    let mut depth = link.components().count().saturating_sub(1);
+
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            _ => return false,
+        }
+    }
+
+    true
+}
@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+
+use super::{ProjectInfo, ProjectServiceError, ProjectServiceTrait};
+
+/// Orders `projects` and all their (transitive) dependencies so every project comes after its dependencies
+pub fn start_order(
+    project_service: &dyn ProjectServiceTrait,
+    projects: &[ProjectInfo],
+) -> super::Result<Vec<ProjectInfo>> {
+    start_order_with(project_service, projects, |project| {
+        Ok(project_service.settings(project)?.dependencies)
+    })
+}
+
+/// Like `start_order`, but dependencies which don't exist (anymore) are left out instead of
+/// failing the whole order, callers check them per project
+pub fn start_order_skipping_missing(
+    project_service: &dyn ProjectServiceTrait,
+    projects: &[ProjectInfo],
+) -> super::Result<Vec<ProjectInfo>> {
+    start_order_with(project_service, projects, |project| {
+        Ok(project_service
+            .settings(project)?
+            .dependencies
+            .into_iter()
+            .filter(|dependency| project_service.project(dependency).is_ok())
+            .collect())
+    })
+}
+
+/// The first dependency of `project` which doesn't exist
+pub fn missing_dependency(
+    project_service: &dyn ProjectServiceTrait,
+    project: &ProjectInfo,
+) -> super::Result<Option<String>> {
+    Ok(project_service
+        .settings(project)?
+        .dependencies
+        .into_iter()
+        .find(|dependency| project_service.project(dependency).is_err()))
+}
+
+/// Checks that `project` can depend on `dependencies` without creating a cycle or referencing unknown projects
+pub fn validate_dependencies(
+    project_service: &dyn ProjectServiceTrait,
+    project: &ProjectInfo,
+    dependencies: &[String],
+) -> super::Result<()> {
+    start_order_with(project_service, std::slice::from_ref(project), |current| {
+        if current.name == project.name {
+            return Ok(dependencies.to_vec());
+        }
+
+        Ok(project_service.settings(current)?.dependencies)
+    })?;
+
+    Ok(())
+}
+
+/// All projects which (transitively) depend on `project`, ordered so they can be stopped one after another
+pub fn dependents(
+    project_service: &dyn ProjectServiceTrait,
+    project: &ProjectInfo,
+) -> super::Result<Vec<ProjectInfo>> {
+    let projects = project_service.all_projects()?;
+
+    let mut reverse: HashMap<String, Vec<ProjectInfo>> = HashMap::new();
+    for current in &projects {
+        for dependency in project_service.settings(current)?.dependencies {
+            reverse.entry(dependency).or_default().push(current.clone());
+        }
+    }
+
+    let mut dependents: Vec<ProjectInfo> = Vec::new();
+    let mut pending = vec![project.name.to_string()];
+    while let Some(name) = pending.pop() {
+        for dependent in reverse.remove(&name).unwrap_or_default() {
+            if !dependents.contains(&dependent) {
+                pending.push(dependent.name.to_string());
+                dependents.push(dependent);
+            }
+        }
+    }
+
+    dependents.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // only the order among the dependents matters, so dependencies outside of them - which
+    // may not even exist anymore - are never looked up
+    let names: HashSet<String> = dependents
+        .iter()
+        .map(|dependent| dependent.name.to_string())
+        .collect();
+    let stop_order = start_order_with(project_service, &dependents, |current| {
+        Ok(project_service
+            .settings(current)?
+            .dependencies
+            .into_iter()
+            .filter(|dependency| names.contains(dependency))
+            .collect())
+    })?
+    .into_iter()
+    .rev()
+    .collect();
+
+    Ok(stop_order)
+}
+
+/// Groups `projects` so that every group only depends on projects of earlier groups.
+/// The projects of one group can therefore be started at the same time
+pub fn start_levels(
+    project_service: &dyn ProjectServiceTrait,
+    projects: &[ProjectInfo],
+) -> super::Result<Vec<Vec<ProjectInfo>>> {
+    let mut levels: HashMap<String, usize> = HashMap::new();
+
+    for project in start_order(project_service, projects)? {
+        let level = project_service
+            .settings(&project)?
+            .dependencies
+            .iter()
+            .filter_map(|dependency| levels.get(dependency))
+            .map(|level| level + 1)
+            .max()
+            .unwrap_or_default();
+
+        levels.insert(project.name.to_string(), level);
+    }
+
+    let mut grouped: Vec<Vec<ProjectInfo>> = Vec::new();
+    for project in projects {
+        let level = levels[&project.name];
+        if grouped.len() <= level {
+            grouped.resize(level + 1, Vec::new());
+        }
+        grouped[level].push(project.clone());
+    }
+
+    Ok(grouped
+        .into_iter()
+        .filter(|group| !group.is_empty())
+        .collect())
+}
+
+fn start_order_with(
+    project_service: &dyn ProjectServiceTrait,
+    projects: &[ProjectInfo],
+    dependencies_of: impl Fn(&ProjectInfo) -> super::Result<Vec<String>>,
+) -> super::Result<Vec<ProjectInfo>> {
+    struct Visit<'a, F> {
+        project_service: &'a dyn ProjectServiceTrait,
+        dependencies_of: F,
+        path: Vec<String>,
+        done: HashSet<String>,
+        order: Vec<ProjectInfo>,
+    }
+
+    impl<F: Fn(&ProjectInfo) -> super::Result<Vec<String>>> Visit<'_, F> {
+        fn visit(&mut self, project: &ProjectInfo) -> super::Result<()> {
+            if self.done.contains(&project.name) {
+                return Ok(());
+            }
+
+            if let Some(position) = self.path.iter().position(|name| *name == project.name) {
+                let cycle = self.path[position..]
+                    .iter()
+                    .chain([&project.name])
+                    .join(" -> ");
+                return Err(ProjectServiceError::DependencyCycle(cycle));
+            }
+
+            self.path.push(project.name.to_string());
+
+            for dependency in (self.dependencies_of)(project)? {
+                let dependency_info = self.project_service.project(&dependency).map_err(|_| {
+                    ProjectServiceError::DependencyNotFound {
+                        project: project.name.to_string(),
+                        dependency: dependency.to_string(),
+                    }
+                })?;
+
+                self.visit(&dependency_info)?;
+            }
+
+            self.path.pop();
+            self.done.insert(project.name.to_string());
+            self.order.push(project.clone());
+
+            Ok(())
+        }
+    }
+
+    let mut visit = Visit {
+        project_service,
+        dependencies_of,
+        path: Vec::new(),
+        done: HashSet::new(),
+        order: Vec::new(),
+    };
+
+    for project in projects {
+        visit.visit(project)?;
+    }
+
+    Ok(visit.order)
+}
@@ -0,0 +1,103 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::{ProjectInfo, glob_matches};
+
+/// File in a root listing the dirs which are no projects, one glob pattern per line
+pub const IGNORE_FILE: &str = ".containeryardignore";
+
+/// A dir is only a project if it holds one of these, in the order docker compose looks for them
+pub const COMPOSE_FILES: &[&str] = &[
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+/// The projects of all roots and the dirs which were passed over
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct Discovery {
+    pub projects: Vec<ProjectInfo>,
+    pub skipped: Vec<SkippedDir>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SkippedDir {
+    pub root: String,
+    pub dir: PathBuf,
+    #[serde(flatten)]
+    pub reason: SkipReason,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum SkipReason {
+    NoComposeFile,
+    Ignored {
+        pattern: String,
+    },
+    /// a symlink pointing out of the root
+    OutsideRoot,
+}
+
+/// Patterns of an ignore file - `#` starts a comment and `!` includes a dir again which an
+/// earlier pattern ignored, the last matching pattern decides
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<(String, bool)>,
+}
+
+impl IgnoreRules {
+    pub fn parse(content: &str) -> IgnoreRules {
+        let rules = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| match line.strip_prefix('!') {
+                Some(pattern) => (pattern.trim_end_matches('/').to_string(), false),
+                None => (line.trim_end_matches('/').to_string(), true),
+            })
+            .collect();
+
+        IgnoreRules { rules }
+    }
+
+    /// Rules of the ignore file in `root`, none if it doesn't exist
+    pub fn read(root: &Path) -> IgnoreRules {
+        let path = root.join(IGNORE_FILE);
+
+        match fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content),
+            Err(err) if err.kind() == ErrorKind::NotFound => IgnoreRules::default(),
+            Err(err) => {
+                warn!("ignoring {:?} - {}", path, err);
+                IgnoreRules::default()
+            }
+        }
+    }
+
+    /// The pattern which ignores the dir `name`, if any
+    pub fn ignoring(&self, name: &str) -> Option<&str> {
+        let (pattern, ignore) = self
+            .rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| glob_matches(name, pattern))?;
+
+        ignore.then_some(pattern.as_str())
+    }
+}
+
+/// The compose file docker compose would pick in `dir`
+pub fn compose_file(dir: &Path) -> Option<&'static str> {
+    COMPOSE_FILES
+        .iter()
+        .find(|file| dir.join(file).is_file())
+        .copied()
+}
@@ -0,0 +1,346 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::{FileFormat, ProjectInfo, ProjectServiceError, ProjectServiceTrait, Result};
+
+/// The env file compose reads variables for `${VAR}` in the compose file from
+pub const DEFAULT_ENV_FILE: &str = ".env";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct EnvEntry {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct EnvFile {
+    /// entries in the order of the file
+    pub entries: Vec<EnvEntry>,
+    /// referenced in a compose file without default value, but not set in the env file
+    pub missing: Vec<String>,
+    /// set in the env file, but neither referenced in a compose file nor passed as `env_file`
+    pub unused: Vec<String>,
+}
+
+/// Content of an env file, an env file which does not exist yet is empty
+pub fn read<P>(project_service: &P, project: &ProjectInfo, file: &str) -> Result<String>
+where
+    P: ProjectServiceTrait + ?Sized,
+{
+    match project_service.read_file(project, file) {
+        Err(ProjectServiceError::FileNotFound { .. }) => Ok(String::new()),
+        result => result,
+    }
+}
+
+/// Content of the compose files in the project dir, including override files
+pub fn compose_files<P>(project_service: &P, project: &ProjectInfo) -> Result<Vec<String>>
+where
+    P: ProjectServiceTrait + ?Sized,
+{
+    project_service
+        .files(project)?
+        .iter()
+        .filter(|file| !file.contains('/') && FileFormat::detect(file, &[]) == FileFormat::Compose)
+        .map(|file| project_service.read_file(project, file))
+        .collect()
+}
+
+/// A line of an env file, comments and blank lines have no entry
+struct EnvLine<'a> {
+    raw: &'a str,
+    export: bool,
+    entry: Option<EnvEntry>,
+}
+
+fn parse_line(raw: &str) -> EnvLine<'_> {
+    let line = raw.trim_start();
+    let (export, line) = match line.strip_prefix("export ") {
+        Some(line) => (true, line.trim_start()),
+        None => (false, line),
+    };
+
+    let entry = (!line.starts_with('#'))
+        .then(|| line.split_once('='))
+        .flatten()
+        .map(|(key, value)| EnvEntry {
+            key: key.trim().to_string(),
+            value: parse_value(value.trim()),
+        })
+        .filter(|entry| !entry.key.is_empty());
+
+    EnvLine { raw, export, entry }
+}
+
+fn parse_value(value: &str) -> String {
+    if let Some(quoted) = value.strip_prefix('\'') {
+        return quoted.split('\'').next().unwrap_or_default().to_string();
+    }
+
+    if let Some(quoted) = value.strip_prefix('"') {
+        let mut parsed = String::new();
+        let mut chars = quoted.chars();
+        while let Some(char) = chars.next() {
+            match char {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => parsed.push('\n'),
+                    Some(other) => parsed.push(other),
+                    None => {}
+                },
+                other => parsed.push(other),
+            }
+        }
+        return parsed;
+    }
+
+    // an unquoted value ends at an inline comment
+    match value.find(" #") {
+        Some(end) => value[..end].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+fn format_value(value: &str) -> String {
+    let is_plain = value
+        .chars()
+        .all(|char| char.is_ascii_alphanumeric() || "_-./:@,+=".contains(char));
+
+    if is_plain {
+        value.to_string()
+    } else if !value.contains('\'') && !value.contains('\n') {
+        format!("'{}'", value)
+    } else {
+        let escaped = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        format!("\"{}\"", escaped)
+    }
+}
+
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|char| char.is_ascii_alphabetic() || char == '_')
+        && chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+/// All entries of the env file, a key set twice keeps its last value like compose does
+pub fn parse(content: &str) -> Vec<EnvEntry> {
+    let mut entries: Vec<EnvEntry> = Vec::new();
+
+    for entry in content.lines().filter_map(|line| parse_line(line).entry) {
+        match entries
+            .iter_mut()
+            .find(|existing| existing.key == entry.key)
+        {
+            Some(existing) => existing.value = entry.value,
+            None => entries.push(entry),
+        }
+    }
+
+    entries
+}
+
+/// Sets the entries of the env file to `entries`. Comments, blank lines and the order of kept
+/// keys stay as they are, removed keys lose their line and new keys are appended
+pub fn update(content: &str, entries: &[EnvEntry]) -> Result<String> {
+    let mut keys = HashSet::new();
+    for entry in entries {
+        if !is_valid_key(&entry.key) {
+            return Err(ProjectServiceError::InvalidEnvKey(entry.key.to_string()));
+        }
+        if !keys.insert(entry.key.as_str()) {
+            return Err(ProjectServiceError::DuplicateEnvKey(entry.key.to_string()));
+        }
+    }
+
+    let mut written = HashSet::new();
+    let mut lines = Vec::new();
+
+    for line in content.lines().map(parse_line) {
+        let Some(old) = line.entry else {
+            lines.push(line.raw.to_string());
+            continue;
+        };
+
+        let Some(new) = entries.iter().find(|entry| entry.key == old.key) else {
+            continue;
+        };
+
+        if !written.insert(new.key.as_str()) {
+            continue;
+        }
+
+        if new.value == old.value {
+            lines.push(line.raw.to_string());
+        } else {
+            let export = if line.export { "export " } else { "" };
+            lines.push(format!(
+                "{}{}={}",
+                export,
+                new.key,
+                format_value(&new.value)
+            ));
+        }
+    }
+
+    for entry in entries {
+        if !written.contains(entry.key.as_str()) {
+            lines.push(format!("{}={}", entry.key, format_value(&entry.value)));
+        }
+    }
+
+    if lines.is_empty() {
+        return Ok(String::new());
+    }
+
+    Ok(lines.join("\n") + "\n")
+}
+
+/// A variable used in a compose file via `${VAR}` or `$VAR`
+#[derive(Debug, PartialEq, Eq)]
+pub struct Reference {
+    pub name: String,
+    /// `${VAR:-default}` and `${VAR:+value}` work without the variable being set
+    pub optional: bool,
+}
+
+pub fn references(compose: &str) -> Vec<Reference> {
+    let mut references = Vec::new();
+
+    for line in compose
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+    {
+        let mut rest = line;
+
+        while let Some(start) = rest.find('$') {
+            rest = &rest[start + 1..];
+
+            if let Some(after) = rest.strip_prefix('$') {
+                // `$$` escapes a literal dollar
+                rest = after;
+            } else if let Some(braced) = rest.strip_prefix('{') {
+                let end = braced.find('}').unwrap_or(braced.len());
+                let expression = &braced[..end];
+                let name_end = expression
+                    .find(|char: char| !(char.is_ascii_alphanumeric() || char == '_'))
+                    .unwrap_or(expression.len());
+                let operator = &expression[name_end..];
+
+                references.push(Reference {
+                    name: expression[..name_end].to_string(),
+                    optional: ["-", ":-", "+", ":+"]
+                        .iter()
+                        .any(|prefix| operator.starts_with(prefix)),
+                });
+                rest = &braced[end..];
+            } else {
+                let end = rest
+                    .find(|char: char| !(char.is_ascii_alphanumeric() || char == '_'))
+                    .unwrap_or(rest.len());
+                references.push(Reference {
+                    name: rest[..end].to_string(),
+                    optional: false,
+                });
+                rest = &rest[end..];
+            }
+        }
+    }
+
+    references.retain(|reference| is_valid_key(&reference.name));
+    references
+}
+
+/// Whether a service of the compose file loads `file` via `env_file`, which passes all its keys on
+pub fn uses_env_file(compose: &str, file: &str) -> bool {
+    let normalize = |value: &str| {
+        let value = value
+            .trim()
+            .trim_matches(|char| char == '"' || char == '\'');
+        value.strip_prefix("./").unwrap_or(value).to_string()
+    };
+
+    let mut lines = compose
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .peekable();
+    while let Some(line) = lines.next() {
+        let Some((indent, value)) = line
+            .find("env_file:")
+            .map(|start| (start, &line[start + "env_file:".len()..]))
+        else {
+            continue;
+        };
+
+        // short syntax `env_file: .env` or a flow list `env_file: [.env, app.env]`
+        let inline = value.trim().trim_start_matches('[').trim_end_matches(']');
+        if inline.split(',').any(|value| normalize(value) == file) {
+            return true;
+        }
+
+        // block list of paths or of `- path: .env` mappings
+        while let Some(next) = lines.next_if(|next| {
+            let next_indent = next.len() - next.trim_start().len();
+            next.trim().is_empty()
+                || next_indent > indent
+                || (next_indent == indent && next.trim_start().starts_with("- "))
+        }) {
+            let item = next.trim().trim_start_matches("- ").trim();
+            let item = item.strip_prefix("path:").unwrap_or(item);
+            if normalize(item) == file {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Compares the entries with the variables the compose files use
+pub fn analyze(entries: Vec<EnvEntry>, file: &str, composes: &[String]) -> EnvFile {
+    let references = composes
+        .iter()
+        .flat_map(|compose| references(compose))
+        .collect_vec();
+    let passed_on = composes.iter().any(|compose| uses_env_file(compose, file));
+
+    // compose only interpolates variables from the default env file
+    let missing = if file == DEFAULT_ENV_FILE {
+        references
+            .iter()
+            .filter(|reference| !reference.optional)
+            .map(|reference| reference.name.to_string())
+            .filter(|name| !entries.iter().any(|entry| entry.key == *name))
+            .unique()
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let unused = if passed_on {
+        Vec::new()
+    } else {
+        entries
+            .iter()
+            .filter(|entry| {
+                file != DEFAULT_ENV_FILE
+                    || !references
+                        .iter()
+                        .any(|reference| reference.name == entry.key)
+            })
+            .map(|entry| entry.key.to_string())
+            .collect()
+    };
+
+    EnvFile {
+        entries,
+        missing,
+        unused,
+    }
+}
@@ -0,0 +1,74 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::{ProjectServiceError, Result};
+
+/// Describes a project for the people working with it, ContainerYard itself doesn't use it
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(default)]
+pub struct ProjectMetadata {
+    pub description: String,
+    pub tags: Vec<String>,
+    pub owner: Option<String>,
+    /// name of an icon or URL of an image
+    pub icon: Option<String>,
+    /// e.g. the public address of the app or its documentation
+    pub links: Vec<ProjectLink>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ProjectLink {
+    pub name: String,
+    pub url: String,
+}
+
+impl ProjectMetadata {
+    /// Trims all values, drops empty and duplicate tags and rejects links which are no http URLs
+    pub fn normalize(self) -> Result<ProjectMetadata> {
+        let optional = |value: Option<String>| {
+            value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        let links = self
+            .links
+            .into_iter()
+            .map(|link| {
+                let url = link.url.trim().to_string();
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    return Err(ProjectServiceError::InvalidMetadata(format!(
+                        "link {} is no http or https URL",
+                        url
+                    )));
+                }
+
+                Ok(ProjectLink {
+                    name: link.name.trim().to_string(),
+                    url,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(ProjectMetadata {
+            description: self.description.trim().to_string(),
+            tags: self
+                .tags
+                .iter()
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .unique_by(|tag| tag.to_lowercase())
+                .collect(),
+            owner: optional(self.owner),
+            icon: optional(self.icon),
+            links,
+        })
+    }
+
+    /// Tags are compared case insensitively
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(tag.trim()))
+    }
+}
@@ -1,11 +1,41 @@
-use std::path::PathBuf;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{ErrorKind, Read, Write},
+    path::PathBuf,
+};
 
 use axum::{Json, http::StatusCode, response::IntoResponse, response::Response};
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
+use super::{notification::NotificationSettings, schedule::Schedule};
+use discovery::Discovery;
+use env::{EnvEntry, EnvFile};
+use metadata::ProjectMetadata;
+use redaction::RedactionPolicy;
+use roots::{ProjectRoot, ROOT_SEPARATOR, RootAction};
+use secrets::{Secret, SecretInfo};
+use templates::Template;
+use trash::TrashEntry;
+
+pub mod archive;
+pub mod dependencies;
+pub mod discovery;
+pub mod env;
+pub mod metadata;
+pub mod redaction;
+pub mod removal;
+pub mod roots;
+pub mod secrets;
 pub mod service;
+pub mod templates;
+pub mod trash;
+
+/// Name of the sidecar file ContainerYard keeps its per-project settings in
+pub const SETTINGS_FILE: &str = ".containeryard.json";
 
 pub type Result<T> = core::result::Result<T, Error>;
 pub type Error = ProjectServiceError;
@@ -18,23 +48,164 @@ pub enum ProjectServiceError {
     #[error("A Project with the name {0} does already Exist")]
     ProjectAlreadyExists(String),
 
-    #[error("Failed to delete Project {0}")]
-    FailedToDeleteProject(String),
+    #[error("Failed to delete Project {0} - {1}")]
+    FailedToDeleteProject(String, ErrorKind),
+
+    #[error("Project {0} is running, stop it first")]
+    ProjectRunning(String),
 
     #[error("Could not find file {file} for {project}")]
     FileNotFound { project: String, file: String },
 
-    #[error("Failed to read directory at {0}")]
-    FailedToReadDir(String),
+    #[error("The file {file} of {project} does already exist")]
+    FileAlreadyExists { project: String, file: String },
+
+    #[error("Could not find {0} in the trash")]
+    TrashEntryNotFound(String),
+
+    #[error("Failed to read directory at {0} - {1}")]
+    FailedToReadDir(String, ErrorKind),
+
+    #[error("Failed to read file at {0} - {1}")]
+    FailedToReadFile(String, ErrorKind),
+
+    #[error("Failed to write file at {0} - {1}")]
+    FailedToWriteFile(String, ErrorKind),
+
+    #[error("Failed to delete file at {0} - {1}")]
+    FailedToDeleteFile(String, ErrorKind),
+
+    #[error("Permission denied for {0}")]
+    PermissionDenied(String),
+
+    #[error("The root {root} does not permit to {action}")]
+    RootPermissionDenied { root: String, action: RootAction },
+
+    #[error("Not enough disk space left to write {0}")]
+    DiskFull(String),
+
+    #[error("{0} is a directory, not a file")]
+    NotAFile(String),
 
-    #[error("Failed to read file at {0}")]
-    FailedToReadFile(String),
+    #[error("{0} is not a directory")]
+    NotADirectory(String),
 
-    #[error("Failed to delete file at {0}")]
-    FailedToDeleteFile(String),
+    #[error("Invalid name {0}")]
+    InvalidName(String),
 
     #[error("Cannot access files outside of project dir - tried to access {0}")]
     InvalidFilePath(String),
+
+    #[error("The file {0} is managed by ContainerYard and cannot be accessed directly")]
+    ReservedFile(String),
+
+    #[error("The file {0} is binary and can only be downloaded")]
+    BinaryFile(String),
+
+    #[error("The file {file} exceeds the maximum size of {max_size} bytes")]
+    FileTooLarge { file: String, max_size: usize },
+
+    #[error("The file {file} was changed in the meantime")]
+    FileChanged {
+        file: String,
+        /// revision of the current content - `None` if the file does not exist anymore
+        revision: Option<String>,
+        content: Option<String>,
+    },
+
+    #[error("Invalid settings for Project {project} - {error}")]
+    InvalidSettings { project: String, error: String },
+
+    #[error("Invalid metadata - {0}")]
+    InvalidMetadata(String),
+
+    #[error("Invalid cursor {0}")]
+    InvalidCursor(String),
+
+    #[error("Invalid archive - {0}")]
+    InvalidArchive(String),
+
+    #[error("Invalid env variable name {0}")]
+    InvalidEnvKey(String),
+
+    #[error("The env variable {0} is set more than once")]
+    DuplicateEnvKey(String),
+
+    #[error("Could not find template {0}")]
+    TemplateNotFound(String),
+
+    #[error("Template {template} requires a value for {parameter}")]
+    MissingTemplateParameter { template: String, parameter: String },
+
+    #[error("Invalid template {template} - {error}")]
+    InvalidTemplate { template: String, error: String },
+
+    #[error("Project {project} depends on unknown Project {dependency}")]
+    DependencyNotFound { project: String, dependency: String },
+
+    #[error("Projects depend on each other in a cycle - {0}")]
+    DependencyCycle(String),
+
+    #[error("Secrets are disabled, no SECRETS_KEY was set")]
+    SecretsDisabled,
+
+    #[error("Could not find secret {name} for {project}")]
+    SecretNotFound { project: String, name: String },
+
+    #[error("Invalid secret name {0}")]
+    InvalidSecretName(String),
+
+    #[error("Failed to encrypt secret {0}")]
+    FailedToEncryptSecret(String),
+
+    #[error("Failed to decrypt secret {0}, it was encrypted with another key")]
+    FailedToDecryptSecret(String),
+}
+
+impl ProjectServiceError {
+    /// Machine readable identifier of the error, returned as `code` next to the message
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProjectServiceError::ProjectNotFound(_) => "project_not_found",
+            ProjectServiceError::ProjectAlreadyExists(_) => "project_already_exists",
+            ProjectServiceError::FailedToDeleteProject(..) => "delete_project_failed",
+            ProjectServiceError::ProjectRunning(_) => "project_running",
+            ProjectServiceError::FileNotFound { .. } => "file_not_found",
+            ProjectServiceError::FileAlreadyExists { .. } => "file_already_exists",
+            ProjectServiceError::TrashEntryNotFound(_) => "trash_entry_not_found",
+            ProjectServiceError::FailedToReadDir(..) => "read_dir_failed",
+            ProjectServiceError::FailedToReadFile(..) => "read_file_failed",
+            ProjectServiceError::FailedToWriteFile(..) => "write_file_failed",
+            ProjectServiceError::FailedToDeleteFile(..) => "delete_file_failed",
+            ProjectServiceError::PermissionDenied(_) => "permission_denied",
+            ProjectServiceError::RootPermissionDenied { .. } => "root_permission_denied",
+            ProjectServiceError::DiskFull(_) => "disk_full",
+            ProjectServiceError::NotAFile(_) => "not_a_file",
+            ProjectServiceError::NotADirectory(_) => "not_a_directory",
+            ProjectServiceError::InvalidName(_) => "invalid_name",
+            ProjectServiceError::InvalidFilePath(_) => "invalid_file_path",
+            ProjectServiceError::ReservedFile(_) => "reserved_file",
+            ProjectServiceError::BinaryFile(_) => "binary_file",
+            ProjectServiceError::FileTooLarge { .. } => "file_too_large",
+            ProjectServiceError::FileChanged { .. } => "file_changed",
+            ProjectServiceError::InvalidSettings { .. } => "invalid_settings",
+            ProjectServiceError::InvalidMetadata(_) => "invalid_metadata",
+            ProjectServiceError::InvalidCursor(_) => "invalid_cursor",
+            ProjectServiceError::InvalidArchive(_) => "invalid_archive",
+            ProjectServiceError::InvalidEnvKey(_) => "invalid_env_key",
+            ProjectServiceError::DuplicateEnvKey(_) => "duplicate_env_key",
+            ProjectServiceError::TemplateNotFound(_) => "template_not_found",
+            ProjectServiceError::MissingTemplateParameter { .. } => "missing_template_parameter",
+            ProjectServiceError::InvalidTemplate { .. } => "invalid_template",
+            ProjectServiceError::DependencyNotFound { .. } => "dependency_not_found",
+            ProjectServiceError::DependencyCycle(_) => "dependency_cycle",
+            ProjectServiceError::SecretsDisabled => "secrets_disabled",
+            ProjectServiceError::SecretNotFound { .. } => "secret_not_found",
+            ProjectServiceError::InvalidSecretName(_) => "invalid_secret_name",
+            ProjectServiceError::FailedToEncryptSecret(_) => "encrypt_secret_failed",
+            ProjectServiceError::FailedToDecryptSecret(_) => "decrypt_secret_failed",
+        }
+    }
 }
 
 impl IntoResponse for ProjectServiceError {
@@ -42,16 +213,56 @@ impl IntoResponse for ProjectServiceError {
         let status = match &self {
             ProjectServiceError::ProjectNotFound(_) => StatusCode::NOT_FOUND,
             ProjectServiceError::FileNotFound { .. } => StatusCode::NOT_FOUND,
-            ProjectServiceError::FailedToReadDir(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            ProjectServiceError::FailedToReadFile(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            ProjectServiceError::FailedToDeleteFile(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ProjectServiceError::FileAlreadyExists { .. } => StatusCode::CONFLICT,
+            ProjectServiceError::TrashEntryNotFound(_) => StatusCode::NOT_FOUND,
+            ProjectServiceError::FailedToReadDir(..) => StatusCode::INTERNAL_SERVER_ERROR,
+            ProjectServiceError::FailedToReadFile(..) => StatusCode::INTERNAL_SERVER_ERROR,
+            ProjectServiceError::FailedToWriteFile(..) => StatusCode::INTERNAL_SERVER_ERROR,
+            ProjectServiceError::FailedToDeleteFile(..) => StatusCode::INTERNAL_SERVER_ERROR,
+            ProjectServiceError::PermissionDenied(_) => StatusCode::FORBIDDEN,
+            ProjectServiceError::RootPermissionDenied { .. } => StatusCode::FORBIDDEN,
+            ProjectServiceError::DiskFull(_) => StatusCode::INSUFFICIENT_STORAGE,
+            ProjectServiceError::NotAFile(_) => StatusCode::BAD_REQUEST,
+            ProjectServiceError::NotADirectory(_) => StatusCode::BAD_REQUEST,
+            ProjectServiceError::InvalidName(_) => StatusCode::BAD_REQUEST,
             ProjectServiceError::InvalidFilePath(_) => StatusCode::BAD_REQUEST,
+            ProjectServiceError::ReservedFile(_) => StatusCode::BAD_REQUEST,
+            ProjectServiceError::BinaryFile(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ProjectServiceError::FileTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            ProjectServiceError::FileChanged { .. } => StatusCode::PRECONDITION_FAILED,
+            ProjectServiceError::InvalidSettings { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            ProjectServiceError::InvalidMetadata(_) => StatusCode::BAD_REQUEST,
+            ProjectServiceError::InvalidCursor(_) => StatusCode::BAD_REQUEST,
+            ProjectServiceError::InvalidArchive(_) => StatusCode::BAD_REQUEST,
+            ProjectServiceError::InvalidEnvKey(_) => StatusCode::BAD_REQUEST,
+            ProjectServiceError::DuplicateEnvKey(_) => StatusCode::BAD_REQUEST,
+            ProjectServiceError::TemplateNotFound(_) => StatusCode::NOT_FOUND,
+            ProjectServiceError::MissingTemplateParameter { .. } => StatusCode::BAD_REQUEST,
+            ProjectServiceError::InvalidTemplate { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            ProjectServiceError::DependencyNotFound { .. } => StatusCode::BAD_REQUEST,
+            ProjectServiceError::DependencyCycle(_) => StatusCode::CONFLICT,
+            ProjectServiceError::SecretsDisabled => StatusCode::SERVICE_UNAVAILABLE,
+            ProjectServiceError::SecretNotFound { .. } => StatusCode::NOT_FOUND,
+            ProjectServiceError::InvalidSecretName(_) => StatusCode::BAD_REQUEST,
+            ProjectServiceError::FailedToEncryptSecret(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ProjectServiceError::FailedToDecryptSecret(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ProjectServiceError::ProjectAlreadyExists(_) => StatusCode::BAD_REQUEST,
-            ProjectServiceError::FailedToDeleteProject(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ProjectServiceError::FailedToDeleteProject(..) => StatusCode::INTERNAL_SERVER_ERROR,
+            ProjectServiceError::ProjectRunning(_) => StatusCode::CONFLICT,
         };
 
-        let body = Json(json!({ "error": self.to_string() }));
-        (status, body).into_response()
+        let mut body = json!({ "error": self.to_string(), "code": self.code() });
+
+        // lets the client resolve the conflict without fetching the file again
+        if let ProjectServiceError::FileChanged {
+            revision, content, ..
+        } = self
+        {
+            body["revision"] = json!(revision);
+            body["content"] = json!(content);
+        }
+
+        (status, Json(body)).into_response()
     }
 }
 
@@ -61,13 +272,321 @@ pub struct ProjectInfo {
     pub dir: PathBuf,
 }
 
+impl ProjectInfo {
+    fn dir_name(&self) -> String {
+        self.dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.name.to_string())
+    }
+
+    /// The root of a project outside the default root
+    pub fn root(&self) -> Option<&str> {
+        self.name
+            .strip_suffix(self.dir_name().as_str())
+            .and_then(|prefix| prefix.strip_suffix(ROOT_SEPARATOR))
+    }
+
+    /// Name docker compose knows the project by. Projects of the default root keep their dir
+    /// name, all others are prefixed with their root, e.g. `infra_web`, so a dir name used in
+    /// several roots doesn't refer to the same containers
+    pub fn compose_name(&self) -> String {
+        match self.root() {
+            Some(root) => format!("{}_{}", root, self.dir_name())
+                .to_lowercase()
+                .chars()
+                .filter(|char| char.is_ascii_alphanumeric() || *char == '_' || *char == '-')
+                .collect::<String>()
+                .trim_start_matches(['_', '-'])
+                .to_string(),
+            None => self.dir_name(),
+        }
+    }
+
+    /// Matches the project name against a pattern where `*` stands for any number of characters and `?` for exactly one
+    pub fn matches(&self, pattern: &str) -> bool {
+        glob_matches(&self.name, pattern)
+    }
+}
+
+/// Matches `name` against a pattern where `*` stands for any number of characters and `?` for exactly one
+pub fn glob_matches(name: &str, pattern: &str) -> bool {
+    fn matches(name: &[char], pattern: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|skip| matches(&name[skip..], rest)),
+            Some((first, rest)) => name.split_first().is_some_and(|(char, name)| {
+                (first == char || *first == '?') && matches(name, rest)
+            }),
+        }
+    }
+
+    let name: Vec<char> = name.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    matches(&name, &pattern)
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FileType {
+    File,
+    Directory,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct FileTreeEntry {
+    pub name: String,
+    /// path relative to the project dir, separated by `/`
+    pub path: String,
+    #[serde(rename = "type")]
+    pub kind: FileType,
+    /// binary files can't be edited as text and have to be downloaded instead
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub binary: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<FileTreeEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FileFormat {
+    Compose,
+    Env,
+    Text,
+    Binary,
+}
+
+impl FileFormat {
+    /// Detects the format from the file name and, for anything else than compose and env files, its content
+    pub fn detect(name: &str, content: &[u8]) -> FileFormat {
+        let is_yaml = name.ends_with(".yml") || name.ends_with(".yaml");
+
+        if is_yaml && (name.starts_with("compose") || name.starts_with("docker-compose")) {
+            FileFormat::Compose
+        } else if is_env_file(name) {
+            FileFormat::Env
+        } else if is_binary(content) {
+            FileFormat::Binary
+        } else {
+            FileFormat::Text
+        }
+    }
+}
+
+/// Env files like `.env`, `.env.production` or `app.env` hold credentials, so they can be
+/// left out of an export
+pub fn is_env_file(name: &str) -> bool {
+    name == ".env" || name.starts_with(".env.") || name.ends_with(".env")
+}
+
+/// Guesses from the start of the content whether it is binary, i.e. contains NUL bytes or is no valid UTF-8
+pub fn is_binary(content: &[u8]) -> bool {
+    let start = &content[..content.len().min(8 * 1024)];
+
+    // a multibyte character may be cut off at the end of the sample
+    start.contains(&0) || std::str::from_utf8(start).is_err_and(|err| err.error_len().is_some())
+}
+
+/// Identifies a version of a file's content, used as its `ETag`
+pub fn revision(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct FileMetadata {
+    /// path relative to the project dir, separated by `/`
+    pub path: String,
+    pub size: u64,
+    pub modified: DateTime<Local>,
+    /// permission bits in octal notation, e.g. `644`
+    pub mode: String,
+    pub format: FileFormat,
+    /// hex encoded SHA-256 of the content, changes whenever the file does. Files larger than
+    /// the max file size are not hashed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+/// What to do if an imported project has the name of an existing one
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NameConflict {
+    #[default]
+    Fail,
+    /// append a number to the name, e.g. `project-2`
+    Rename,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum DesiredState {
+    Running,
+    Stopped,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(default)]
+pub struct ProjectSettings {
+    pub notifications: NotificationSettings,
+    pub schedules: Vec<Schedule>,
+    /// pull and restart the project as soon as newer images are available
+    pub auto_update: bool,
+    /// the state the project was last put into through ContainerYard - `None` if it never was
+    pub desired_state: Option<DesiredState>,
+    /// names of the projects which have to run before this one can start
+    pub dependencies: Vec<String>,
+    pub metadata: ProjectMetadata,
+    /// encrypted secrets by their name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub secrets: BTreeMap<String, Secret>,
+}
+
 pub trait ProjectServiceTrait: Send + Sync {
-    fn all_projects(&self) -> Result<Vec<ProjectInfo>>;
+    /// Dirs of all roots which hold a compose file and are not ignored, together with the skipped ones
+    fn discover(&self) -> Result<Discovery>;
+    /// Any project dir, also one which discovery skips so it can still be fixed
     fn project(&self, name: &str) -> Result<ProjectInfo>;
     fn create(&self, name: &str) -> Result<ProjectInfo>;
+    /// Built-in templates and the ones of the template dir, which replace built-ins of the same name
+    fn templates(&self) -> Result<Vec<Template>>;
+    /// Creates a project with the rendered files of the template
+    fn create_from_template(
+        &self,
+        name: &str,
+        template: &str,
+        variables: &HashMap<String, String>,
+    ) -> Result<ProjectInfo>;
+    /// Moves the project into the trash
     fn delete(&self, project: &ProjectInfo) -> Result<()>;
+    /// Moves the project to a new directory and updates the dependencies of other projects
+    fn rename(&self, project: &ProjectInfo, new_name: &str) -> Result<ProjectInfo>;
+    /// Copies all files of the project into a new project, without its settings
+    fn duplicate(&self, project: &ProjectInfo, new_name: &str) -> Result<ProjectInfo>;
+    /// Writes the project directory as tar.gz, optionally without env files
+    fn export(
+        &self,
+        project: &ProjectInfo,
+        exclude_env: bool,
+        writer: &mut dyn Write,
+    ) -> Result<()>;
+    /// Unpacks a tar.gz created by `export` into a new project
+    fn import(
+        &self,
+        name: &str,
+        conflict: NameConflict,
+        archive: &mut dyn Read,
+    ) -> Result<ProjectInfo>;
+    /// Paths of all files of the project, including the ones in sub directories
     fn files(&self, project: &ProjectInfo) -> Result<Vec<String>>;
+    fn tree(&self, project: &ProjectInfo) -> Result<Vec<FileTreeEntry>>;
+    fn file_metadata(&self, project: &ProjectInfo, file: &str) -> Result<FileMetadata>;
     fn read_file(&self, project: &ProjectInfo, file: &str) -> Result<String>;
     fn update_file(&self, project: &ProjectInfo, file: &str, content: &str) -> Result<String>;
+    /// Only updates the file if its current content has one of the given revisions, `*` matches any existing file
+    fn update_file_if_match(
+        &self,
+        project: &ProjectInfo,
+        file: &str,
+        content: &str,
+        revisions: &[String],
+    ) -> Result<String>;
+    /// Resolved path of an existing file, e.g. to stream its raw content
+    fn file_path(&self, project: &ProjectInfo, file: &str) -> Result<PathBuf>;
+    fn write_bytes(&self, project: &ProjectInfo, file: &str, content: &[u8]) -> Result<()>;
+    fn max_file_size(&self) -> usize;
+    /// Keys whose values are masked for users who may not reveal secrets
+    fn redaction_policy(&self) -> &RedactionPolicy;
     fn delete_file(&self, project: &ProjectInfo, file: &str) -> Result<()>;
+    fn create_dir(&self, project: &ProjectInfo, dir: &str) -> Result<()>;
+    fn delete_dir(&self, project: &ProjectInfo, dir: &str) -> Result<()>;
+    /// Directories holding projects, the default root first
+    fn roots(&self) -> Vec<ProjectRoot>;
+    /// Fails if the root of the project doesn't permit `action`
+    fn check_permission(&self, name: &str, action: RootAction) -> Result<()>;
+    /// Deleted projects, files and directories, the most recently deleted first
+    fn trash(&self) -> Result<Vec<TrashEntry>>;
+    /// Moves a deleted project or file back to where it was deleted from
+    fn restore(&self, id: &str) -> Result<TrashEntry>;
+    fn purge(&self, id: &str) -> Result<TrashEntry>;
+    /// Finally deletes everything kept longer than the retention period
+    fn purge_expired(&self) -> Result<Vec<TrashEntry>>;
+    fn settings(&self, project: &ProjectInfo) -> Result<ProjectSettings>;
+    /// Reads the settings, lets `modify` change them and writes them back while holding the
+    /// write lock, so concurrent changes of a project's settings can't overwrite each other.
+    /// Nothing is written if `modify` fails or changes nothing in an existing file
+    fn modify_settings(
+        &self,
+        project: &ProjectInfo,
+        modify: &mut dyn FnMut(&mut ProjectSettings) -> Result<()>,
+    ) -> Result<ProjectSettings>;
+    /// Names of the secrets of the project, their values are never returned
+    fn secrets(&self, project: &ProjectInfo) -> Result<Vec<SecretInfo>>;
+    /// Encrypts and stores the value, replacing the secret if it exists
+    fn set_secret(&self, project: &ProjectInfo, name: &str, value: &str) -> Result<SecretInfo>;
+    fn delete_secret(&self, project: &ProjectInfo, name: &str) -> Result<()>;
+    /// Writes the decrypted secrets into the secrets dir of the project, right before it starts
+    fn materialize_secrets(&self, project: &ProjectInfo) -> Result<()>;
+    /// Removes the decrypted secrets once the project is stopped
+    fn clear_secrets(&self, project: &ProjectInfo) -> Result<()>;
+
+    /// The discovered projects of all roots, sorted by name
+    fn all_projects(&self) -> Result<Vec<ProjectInfo>> {
+        Ok(self.discover()?.projects)
+    }
+
+    /// Entries of an env file together with the variables missing in it or not used by compose
+    fn env(&self, project: &ProjectInfo, file: &str) -> Result<EnvFile> {
+        let content = env::read(self, project, file)?;
+        Ok(env::analyze(
+            env::parse(&content),
+            file,
+            &env::compose_files(self, project)?,
+        ))
+    }
+
+    /// Replaces the entries of an env file, comments and the order of kept keys are preserved
+    fn update_env(
+        &self,
+        project: &ProjectInfo,
+        file: &str,
+        entries: &[EnvEntry],
+    ) -> Result<EnvFile> {
+        let content = env::read(self, project, file)?;
+        self.update_file(project, file, &env::update(&content, entries)?)?;
+        self.env(project, file)
+    }
+
+    /// Replaces the metadata of the project with its normalized form
+    fn update_metadata(
+        &self,
+        project: &ProjectInfo,
+        metadata: ProjectMetadata,
+    ) -> Result<ProjectMetadata> {
+        let metadata = metadata.normalize()?;
+        let settings = self.modify_settings(project, &mut |settings| {
+            settings.metadata = metadata.clone();
+            Ok(())
+        })?;
+
+        Ok(settings.metadata)
+    }
+
+    /// Replaces all settings at once
+    fn update_settings(&self, project: &ProjectInfo, settings: &ProjectSettings) -> Result<()> {
+        self.modify_settings(project, &mut |current| {
+            *current = settings.clone();
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    fn set_desired_state(&self, project: &ProjectInfo, state: DesiredState) -> Result<()> {
+        self.modify_settings(project, &mut |settings| {
+            settings.desired_state = Some(state);
+            Ok(())
+        })?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,183 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::glob_matches;
+
+/// Replaces the value of every sensitive key for users who may not see it
+pub const MASK: &str = "********";
+
+/// Keys masked unless configured otherwise
+pub const DEFAULT_PATTERNS: &[&str] = &["*PASSWORD*", "*TOKEN*", "*SECRET*", "*_KEY"];
+
+/// Which keys hold sensitive values - a pattern matches a key case insensitively, `*`
+/// stands for any number of characters and `?` for exactly one
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedactionPolicy {
+    patterns: Vec<String>,
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_PATTERNS.iter().map(|pattern| pattern.to_string()))
+    }
+}
+
+/// Byte ranges of a `KEY=value` or `KEY: value` assignment inside a line
+struct Assignment<'a> {
+    key: &'a str,
+    value_start: usize,
+    value_end: usize,
+}
+
+impl RedactionPolicy {
+    pub fn new(patterns: impl IntoIterator<Item = String>) -> RedactionPolicy {
+        RedactionPolicy {
+            patterns: patterns
+                .into_iter()
+                .map(|pattern| pattern.trim().to_ascii_uppercase())
+                .filter(|pattern| !pattern.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_sensitive(&self, key: &str) -> bool {
+        let key = key.to_ascii_uppercase();
+        self.patterns
+            .iter()
+            .any(|pattern| glob_matches(&key, pattern))
+    }
+
+    /// Masks the values of sensitive keys, in env files as well as in compose files
+    pub fn mask(&self, content: &str) -> String {
+        self.map_lines(content, |_, _| Some(MASK.to_string()))
+    }
+
+    /// Puts the values of `original` back where `content` still holds the mask, so a file
+    /// edited in its masked form keeps the values the editor never saw
+    pub fn unmask(&self, content: &str, original: &str) -> String {
+        let mut values: HashMap<&str, VecDeque<&str>> = HashMap::new();
+        for line in original.lines() {
+            if let Some(assignment) = self.sensitive_assignment(line) {
+                values
+                    .entry(assignment.key)
+                    .or_default()
+                    .push_back(&line[assignment.value_start..assignment.value_end]);
+            }
+        }
+
+        self.map_lines(content, |key, value| {
+            if value != MASK {
+                return None;
+            }
+
+            // a key set more often than before gets its last value again
+            let original = values.get_mut(key)?;
+            match original.len() {
+                0 => None,
+                1 => original.front().map(|value| value.to_string()),
+                _ => original.pop_front().map(str::to_string),
+            }
+        })
+    }
+
+    /// Replaces the values of sensitive keys with the result of `replace`, if it returns one
+    fn map_lines(
+        &self,
+        content: &str,
+        mut replace: impl FnMut(&str, &str) -> Option<String>,
+    ) -> String {
+        content
+            .split_inclusive('\n')
+            .map(|line| {
+                let Some(assignment) = self.sensitive_assignment(line) else {
+                    return line.to_string();
+                };
+                let value = &line[assignment.value_start..assignment.value_end];
+
+                match replace(assignment.key, value) {
+                    Some(replaced) => format!(
+                        "{}{}{}",
+                        &line[..assignment.value_start],
+                        replaced,
+                        &line[assignment.value_end..]
+                    ),
+                    None => line.to_string(),
+                }
+            })
+            .collect()
+    }
+
+    fn sensitive_assignment<'a>(&self, line: &'a str) -> Option<Assignment<'a>> {
+        parse_assignment(line).filter(|assignment| self.is_sensitive(assignment.key))
+    }
+}
+
+/// Finds `KEY=value`, `export KEY=value`, `- KEY=value`, `- "KEY=value"` and `KEY: value`,
+/// also behind a `#`
+fn parse_assignment(line: &str) -> Option<Assignment<'_>> {
+    let text = line.trim_end();
+    let mut start = text.len() - text.trim_start().len();
+
+    // commented out assignments hold real values just as well
+    for prefix in ["#", "- ", "export "] {
+        if text[start..].starts_with(prefix) {
+            start += prefix.len();
+            start += text[start..].len() - text[start..].trim_start().len();
+        }
+    }
+
+    let quote = text[start..]
+        .chars()
+        .next()
+        .filter(|char| *char == '"' || *char == '\'');
+    if quote.is_some() {
+        start += 1;
+    }
+
+    let key_len = text[start..]
+        .find(|char: char| !(char.is_ascii_alphanumeric() || "_.-".contains(char)))
+        .unwrap_or(text.len() - start);
+    let key = &text[start..start + key_len];
+    let after_key = start + key_len;
+    let separator = text[after_key..].trim_start();
+    let separator_start = text.len() - separator.len();
+
+    let value_start = match separator.chars().next()? {
+        '=' => separator_start + 1,
+        // `key:value` is no mapping in yaml
+        ':' if separator[1..].is_empty() || separator[1..].starts_with(' ') => separator_start + 1,
+        _ => return None,
+    };
+    let value_start =
+        value_start + (text[value_start..].len() - text[value_start..].trim_start().len());
+
+    let mut value_end = text.len();
+    if let Some(quote) = quote {
+        value_end = text[value_start..]
+            .rfind(quote)
+            .map(|end| value_start + end)
+            .unwrap_or(value_end);
+    } else if let Some(value_quote) = text[value_start..]
+        .chars()
+        .next()
+        .filter(|char| *char == '"' || *char == '\'')
+    {
+        // a quoted value ends at its closing quote, a comment may follow
+        if let Some(end) = text[value_start + 1..].find(value_quote) {
+            value_end = value_start + 1 + end + 1;
+        }
+    } else if let Some(comment) = text[value_start..].find(" #") {
+        // an unquoted value ends at an inline comment
+        value_end = value_start + comment;
+    }
+
+    let value = &text[value_start..value_end];
+    if key.is_empty() || value.trim().is_empty() {
+        return None;
+    }
+
+    Some(Assignment {
+        key,
+        value_start,
+        value_end: value_start + value.trim_end().len(),
+    })
+}
@@ -0,0 +1,85 @@
+use serde::Serialize;
+
+use crate::services::container::{ContainerServiceTrait, DownOptions};
+
+use super::{ProjectInfo, ProjectServiceTrait};
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteStepKind {
+    /// `docker compose down` for containers, networks and optionally volumes and images
+    Down,
+    /// moving the project directory into the trash
+    Trash,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteStepStatus {
+    Ok,
+    Failed,
+    /// not run because a previous step failed
+    Skipped,
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct DeleteStep {
+    pub step: DeleteStepKind,
+    pub status: DeleteStepStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DeleteStep {
+    fn new<E: ToString>(step: DeleteStepKind, result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => DeleteStep {
+                step,
+                status: DeleteStepStatus::Ok,
+                error: None,
+            },
+            Err(err) => DeleteStep {
+                step,
+                status: DeleteStepStatus::Failed,
+                error: Some(err.to_string()),
+            },
+        }
+    }
+}
+
+/// Tears down the containers of the project if `down` is set and moves it into the trash.
+/// The project is kept if its containers could not be removed, as they could not be managed
+/// anymore without the compose file. Every step reports its own outcome
+pub fn delete_project(
+    project_service: &dyn ProjectServiceTrait,
+    container_service: &dyn ContainerServiceTrait,
+    project: &ProjectInfo,
+    down: Option<&DownOptions>,
+) -> Vec<DeleteStep> {
+    let mut steps = Vec::new();
+
+    if let Some(options) = down {
+        let step = DeleteStep::new(
+            DeleteStepKind::Down,
+            container_service.down(project, options),
+        );
+        let failed = step.status == DeleteStepStatus::Failed;
+        steps.push(step);
+
+        if failed {
+            steps.push(DeleteStep {
+                step: DeleteStepKind::Trash,
+                status: DeleteStepStatus::Skipped,
+                error: None,
+            });
+            return steps;
+        }
+    }
+
+    steps.push(DeleteStep::new(
+        DeleteStepKind::Trash,
+        project_service.delete(project),
+    ));
+
+    steps
+}
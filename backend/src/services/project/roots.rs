@@ -0,0 +1,141 @@
+use std::{fmt, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Name of the root given by `ProjectService::new`, its projects are addressed without prefix
+pub const DEFAULT_ROOT: &str = "default";
+
+/// Separates the root from the project in the name of a project outside the default root,
+/// e.g. `infra:traefik`
+pub const ROOT_SEPARATOR: char = ':';
+
+/// A directory holding projects
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ProjectRoot {
+    pub name: String,
+    pub path: PathBuf,
+    pub permissions: RootPermissions,
+}
+
+impl ProjectRoot {
+    pub fn new(name: &str, path: PathBuf) -> ProjectRoot {
+        ProjectRoot {
+            name: name.to_string(),
+            path,
+            permissions: RootPermissions::ALL,
+        }
+    }
+
+    pub fn with_permissions(mut self, permissions: RootPermissions) -> ProjectRoot {
+        self.permissions = permissions;
+        self
+    }
+}
+
+/// What may be changed inside a root through ContainerYard. The desired state and the last
+/// runs of schedules are recorded in every root regardless, as running a project changes them
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RootPermissions {
+    /// create, import, duplicate and restore projects
+    pub create: bool,
+    /// write files, create directories and change settings like schedules or secrets
+    pub edit: bool,
+    /// delete projects, files and directories
+    pub delete: bool,
+}
+
+impl RootPermissions {
+    pub const ALL: RootPermissions = RootPermissions {
+        create: true,
+        edit: true,
+        delete: true,
+    };
+
+    pub const READ_ONLY: RootPermissions = RootPermissions {
+        create: false,
+        edit: false,
+        delete: false,
+    };
+
+    pub fn allows(&self, action: RootAction) -> bool {
+        match action {
+            RootAction::Create => self.create,
+            RootAction::Edit => self.edit,
+            RootAction::Delete => self.delete,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RootAction {
+    Create,
+    Edit,
+    Delete,
+}
+
+impl fmt::Display for RootAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RootAction::Create => write!(f, "create projects"),
+            RootAction::Edit => write!(f, "edit files or settings"),
+            RootAction::Delete => write!(f, "delete projects or files"),
+        }
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_')
+}
+
+/// Parses roots like `apps=/srv/apps,infra=/opt/infra:ro,backup=/mnt/backup:create+edit`.
+/// Without permissions a root allows everything, `ro` nothing
+pub fn parse_roots(value: &str) -> Result<Vec<ProjectRoot>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|root| !root.is_empty())
+        .map(|root| {
+            let (name, path) = root
+                .split_once('=')
+                .ok_or_else(|| format!("root {} has no path, use name=path", root))?;
+            let name = name.trim();
+
+            if !is_valid_name(name) {
+                return Err(format!("invalid root name {}", name));
+            }
+
+            let (path, permissions) = match path.rsplit_once(':') {
+                Some((path, flags)) => match parse_permissions(flags) {
+                    Some(permissions) => (path, permissions),
+                    None => return Err(format!("invalid permissions {} for root {}", flags, name)),
+                },
+                None => (path, RootPermissions::ALL),
+            };
+
+            Ok(ProjectRoot::new(name, PathBuf::from(path.trim())).with_permissions(permissions))
+        })
+        .collect()
+}
+
+fn parse_permissions(flags: &str) -> Option<RootPermissions> {
+    match flags {
+        "ro" => return Some(RootPermissions::READ_ONLY),
+        "rw" => return Some(RootPermissions::ALL),
+        _ => {}
+    }
+
+    let mut permissions = RootPermissions::READ_ONLY;
+    for flag in flags.split('+') {
+        match flag {
+            "create" => permissions.create = true,
+            "edit" => permissions.edit = true,
+            "delete" => permissions.delete = true,
+            _ => return None,
+        }
+    }
+
+    Some(permissions)
+}
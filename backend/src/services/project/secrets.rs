@@ -0,0 +1,182 @@
+use std::{
+    collections::HashMap,
+    fs::{DirBuilder, OpenOptions, remove_dir_all},
+    io::{ErrorKind, Write},
+    num::NonZeroU32,
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Local};
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    pbkdf2::{self, PBKDF2_HMAC_SHA256},
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{ProjectServiceError, Result, service::io_error};
+
+/// Directory inside the project the secrets are written to in plaintext while it runs
+pub const SECRETS_DIR: &str = ".containeryard-secrets";
+
+/// Env file inside `SECRETS_DIR` with one `NAME=value` line per secret, for `env_file`
+pub const SECRETS_ENV_FILE: &str = "secrets.env";
+
+/// Length of the random salt stored in front of every encrypted value
+pub const SALT_LEN: usize = 16;
+
+/// PBKDF2 iterations to derive a key from the passphrase and the salt of a value
+const KDF_ITERATIONS: NonZeroU32 = NonZeroU32::new(100_000).unwrap();
+
+/// A secret as stored in the project settings, only the encrypted value is kept
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Secret {
+    /// base64 of the salt, the nonce and the ciphertext
+    pub value: String,
+    pub updated: DateTime<Local>,
+}
+
+/// What the API returns about a secret - never its value
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SecretInfo {
+    pub name: String,
+    pub updated: DateTime<Local>,
+}
+
+/// Passphrase all secrets are encrypted with, every value gets its own key derived from it
+/// and a random salt
+#[derive(Clone)]
+pub struct SecretsKey {
+    passphrase: String,
+    /// Derived keys by salt, so a project's secrets aren't derived again on every start
+    keys: Arc<Mutex<HashMap<[u8; SALT_LEN], [u8; 32]>>>,
+}
+
+impl SecretsKey {
+    pub fn new(passphrase: &str) -> SecretsKey {
+        SecretsKey {
+            passphrase: passphrase.to_string(),
+            keys: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn aead_key(&self, salt: [u8; SALT_LEN]) -> LessSafeKey {
+        let mut keys = self.keys.lock().expect("secrets key lock poisoned");
+        let key = keys.entry(salt).or_insert_with(|| {
+            let mut key = [0u8; 32];
+            pbkdf2::derive(
+                PBKDF2_HMAC_SHA256,
+                KDF_ITERATIONS,
+                &salt,
+                self.passphrase.as_bytes(),
+                &mut key,
+            );
+            key
+        });
+
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).expect("key has 32 bytes"))
+    }
+
+    /// Encrypts the value with a random salt and nonce, the name is authenticated so a value
+    /// can't be moved over to another secret
+    pub fn encrypt(&self, name: &str, value: &str) -> Result<String> {
+        let failed = || ProjectServiceError::FailedToEncryptSecret(name.to_string());
+
+        let random = SystemRandom::new();
+        let mut salt = [0u8; SALT_LEN];
+        random.fill(&mut salt).map_err(|_| failed())?;
+        let mut nonce = [0u8; NONCE_LEN];
+        random.fill(&mut nonce).map_err(|_| failed())?;
+
+        let mut sealed = value.as_bytes().to_vec();
+        self.aead_key(salt)
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(name.as_bytes()),
+                &mut sealed,
+            )
+            .map_err(|_| failed())?;
+
+        Ok(STANDARD.encode([salt.as_slice(), &nonce, &sealed].concat()))
+    }
+
+    /// Fails if the value was encrypted with another key or was tampered with
+    pub fn decrypt(&self, name: &str, value: &str) -> Result<String> {
+        let failed = || ProjectServiceError::FailedToDecryptSecret(name.to_string());
+
+        let mut sealed = STANDARD.decode(value).map_err(|_| failed())?;
+        if sealed.len() < SALT_LEN + NONCE_LEN {
+            return Err(failed());
+        }
+        let mut ciphertext = sealed.split_off(SALT_LEN + NONCE_LEN);
+        let (salt, nonce) = sealed.split_at(SALT_LEN);
+        let salt = salt.try_into().map_err(|_| failed())?;
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| failed())?;
+
+        let plaintext = self
+            .aead_key(salt)
+            .open_in_place(nonce, Aad::from(name.as_bytes()), &mut ciphertext)
+            .map_err(|_| failed())?;
+
+        String::from_utf8(plaintext.to_vec()).map_err(|_| failed())
+    }
+}
+
+/// Secret names end up as env variables and file names, so they follow the env variable rules
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|char| char.is_ascii_alphabetic() || char == '_')
+        && chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+/// Replaces the content of `SECRETS_DIR` with one file per secret and the env file of all
+/// secrets. Only the owner can access the dir and its files
+pub fn materialize(project_dir: &Path, secrets: &[(String, String)]) -> Result<()> {
+    let dir = project_dir.join(SECRETS_DIR);
+    clear(project_dir)?;
+
+    DirBuilder::new()
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|err| io_error(&dir, err, ProjectServiceError::FailedToWriteFile))?;
+
+    let write = |name: &str, content: &str| {
+        let path = dir.join(name);
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+            .and_then(|mut file| file.write_all(content.as_bytes()))
+            .map_err(|err| io_error(&path, err, ProjectServiceError::FailedToWriteFile))
+    };
+
+    let mut env = String::new();
+    for (name, value) in secrets {
+        write(name, value)?;
+
+        let escaped = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        env.push_str(&format!("{}=\"{}\"\n", name, escaped));
+    }
+    write(SECRETS_ENV_FILE, &env)
+}
+
+/// Removes the plaintext secrets of the project again
+pub fn clear(project_dir: &Path) -> Result<()> {
+    let dir = project_dir.join(SECRETS_DIR);
+
+    match remove_dir_all(&dir) {
+        Err(err) if err.kind() != ErrorKind::NotFound => {
+            Err(io_error(&dir, err, ProjectServiceError::FailedToDeleteFile))
+        }
+        _ => Ok(()),
+    }
+}
@@ -1,60 +1,537 @@
 use std::{
-    fs::{self, File, create_dir, remove_dir_all, remove_file},
-    io::Write,
-    path::PathBuf,
+    collections::HashMap,
+    fs::{self, File, OpenOptions, create_dir, create_dir_all, remove_dir_all, remove_file},
+    io::{self, ErrorKind, Read, Write},
+    os::unix::fs::{MetadataExt, PermissionsExt, fchown, symlink},
+    path::{Component, Path, PathBuf},
+    process,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
 };
 
+use chrono::{Local, TimeDelta};
 use itertools::Itertools;
-use tracing::error;
+use tracing::{error, warn};
 
-use super::{ProjectInfo, ProjectServiceError, ProjectServiceTrait};
+use super::{
+    FileFormat, FileMetadata, FileTreeEntry, FileType, NameConflict, ProjectInfo,
+    ProjectServiceError, ProjectServiceTrait, ProjectSettings, SETTINGS_FILE, archive,
+    discovery::{Discovery, IgnoreRules, SkipReason, SkippedDir, compose_file},
+    is_binary,
+    redaction::RedactionPolicy,
+    revision,
+    roots::{DEFAULT_ROOT, ProjectRoot, ROOT_SEPARATOR, RootAction},
+    secrets::{self, SECRETS_DIR, Secret, SecretInfo, SecretsKey},
+    templates::{Template, builtin_templates, read_template},
+    trash::{TRASH_DIR, Trash, TrashEntry},
+};
+
+/// Files larger than this are rejected unless configured otherwise
+pub const DEFAULT_MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
+
+/// Deleted projects and files are purged after this unless configured otherwise
+pub const DEFAULT_TRASH_RETENTION: TimeDelta = TimeDelta::days(30);
+
+/// A root together with the trash of the items deleted from it, which has to be on the same
+/// file system to move them there
+struct Root {
+    root: ProjectRoot,
+    trash: Trash,
+}
+
+impl Root {
+    fn new(root: ProjectRoot) -> Root {
+        Root {
+            trash: Trash::new(root.path.join(TRASH_DIR)),
+            root,
+        }
+    }
+
+    fn is_default(&self) -> bool {
+        self.root.name == DEFAULT_ROOT
+    }
+
+    /// Projects of the default root keep their plain dir name, all others are prefixed with their root
+    fn project_name(&self, dir: &str) -> String {
+        if self.is_default() {
+            dir.to_string()
+        } else {
+            format!("{}{}{}", self.root.name, ROOT_SEPARATOR, dir)
+        }
+    }
+}
 
-#[derive(Default)]
 pub struct ProjectService {
-    base_path: PathBuf,
+    /// the default root always comes first
+    roots: Vec<Root>,
+    max_file_size: usize,
+    /// holds user defined templates, one directory per template
+    template_dir: Option<PathBuf>,
+    /// how long deleted items are kept, `None` keeps them until they are purged
+    trash_retention: Option<TimeDelta>,
+    /// encrypts the secrets of all projects, secrets are disabled without it
+    secrets_key: Option<SecretsKey>,
+    redaction_policy: RedactionPolicy,
+    /// serializes writes, so a revision check and the following write can't interleave with another write
+    write_lock: Mutex<()>,
+}
+
+impl Default for ProjectService {
+    fn default() -> Self {
+        Self::new(PathBuf::default())
+    }
 }
 
 impl ProjectService {
     pub fn new(base_path: PathBuf) -> ProjectService {
-        Self { base_path }
+        Self {
+            roots: vec![Root::new(ProjectRoot::new(DEFAULT_ROOT, base_path))],
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            template_dir: None,
+            trash_retention: Some(DEFAULT_TRASH_RETENTION),
+            secrets_key: None,
+            redaction_policy: RedactionPolicy::default(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Adds a root or replaces the one with the same name, including the default root
+    pub fn with_root(mut self, root: ProjectRoot) -> ProjectService {
+        match self
+            .roots
+            .iter_mut()
+            .find(|existing| existing.root.name == root.name)
+        {
+            Some(existing) => *existing = Root::new(root),
+            None => self.roots.push(Root::new(root)),
+        }
+        self
+    }
+
+    pub fn with_max_file_size(mut self, max_file_size: usize) -> ProjectService {
+        self.max_file_size = max_file_size;
+        self
     }
 
+    pub fn with_trash_retention(mut self, trash_retention: Option<TimeDelta>) -> ProjectService {
+        self.trash_retention = trash_retention;
+        self
+    }
+
+    pub fn with_template_dir(mut self, template_dir: PathBuf) -> ProjectService {
+        self.template_dir = Some(template_dir);
+        self
+    }
+
+    pub fn with_secrets_key(mut self, secrets_key: SecretsKey) -> ProjectService {
+        self.secrets_key = Some(secrets_key);
+        self
+    }
+
+    pub fn with_redaction_policy(mut self, redaction_policy: RedactionPolicy) -> ProjectService {
+        self.redaction_policy = redaction_policy;
+        self
+    }
+
+    /// Relative path inside a project dir - nested paths are allowed, leaving the dir is not
     pub fn save_file_path(file: &str) -> super::Result<PathBuf> {
         let path = PathBuf::from(file);
-        if path.components().count() != 1 {
+        let is_relative = path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+
+        if file.is_empty() || !is_relative {
             return Err(ProjectServiceError::InvalidFilePath(file.to_string()));
         }
 
+        if path == Path::new(SETTINGS_FILE) || path.starts_with(SECRETS_DIR) {
+            return Err(ProjectServiceError::ReservedFile(file.to_string()));
+        }
+
         Ok(path)
     }
-}
 
-impl ProjectServiceTrait for ProjectService {
-    fn all_projects(&self) -> super::Result<Vec<ProjectInfo>> {
-        let dir = fs::read_dir(&self.base_path)
-            .inspect_err(|err| error!("{}", err))
-            .map_err(|_| ProjectServiceError::FailedToReadDir(format!("{:?}", self.base_path)))?;
+    /// Resolves `file` inside the project dir and rejects paths which leave it, also through symlinks
+    pub fn resolve_path(project: &ProjectInfo, file: &str) -> super::Result<PathBuf> {
+        let relative = Self::save_file_path(file)?;
+
+        let root = project
+            .dir
+            .canonicalize()
+            .map_err(|_| ProjectServiceError::ProjectNotFound(project.name.to_string()))?;
+        let path = root.join(relative);
 
-        let projects = dir
+        // the deepest existing part decides where the path really points to,
+        // the missing rest only consists of normal components
+        let existing = path
+            .ancestors()
+            .find(|ancestor| fs::symlink_metadata(ancestor).is_ok())
+            .unwrap_or(&root);
+
+        let Ok(resolved) = existing.canonicalize() else {
+            return Err(ProjectServiceError::InvalidFilePath(file.to_string()));
+        };
+
+        if !resolved.starts_with(&root) {
+            return Err(ProjectServiceError::InvalidFilePath(file.to_string()));
+        }
+
+        if resolved == root.join(SETTINGS_FILE) || resolved.starts_with(root.join(SECRETS_DIR)) {
+            return Err(ProjectServiceError::ReservedFile(file.to_string()));
+        }
+
+        Ok(path)
+    }
+
+    /// The root a project name points to and the name of the dir inside it. A prefix which
+    /// names no root is part of the dir name, so the default root can still hold such dirs
+    fn split_name<'a>(&self, name: &'a str) -> (&Root, &'a str) {
+        name.split_once(ROOT_SEPARATOR)
+            .and_then(|(prefix, dir)| {
+                self.roots
+                    .iter()
+                    .find(|root| !root.is_default() && root.root.name == prefix)
+                    .map(|root| (root, dir))
+            })
+            .unwrap_or((&self.roots[0], name))
+    }
+
+    /// The trash holding the entry with the given id
+    fn trash_of(&self, id: &str) -> super::Result<&Trash> {
+        self.roots
+            .iter()
+            .map(|root| &root.trash)
+            .find(|trash| trash.entry(id).is_ok())
+            .ok_or_else(|| ProjectServiceError::TrashEntryNotFound(id.to_string()))
+    }
+
+    /// Path of the project dir with the given name, which has to stay inside its root
+    fn project_path(&self, name: &str) -> super::Result<PathBuf> {
+        let invalid = || ProjectServiceError::InvalidName(name.to_string());
+        let (root, dir) = self.split_name(name);
+        let base_path = &root.root.path;
+
+        let mut components = Path::new(dir).components();
+        let (Some(Component::Normal(_)), None) = (components.next(), components.next()) else {
+            return Err(invalid());
+        };
+
+        if dir == TRASH_DIR {
+            return Err(invalid());
+        }
+
+        let path = base_path.join(dir);
+        if fs::symlink_metadata(&path).is_ok() && !is_inside(base_path, &path) {
+            return Err(invalid());
+        }
+
+        Ok(path)
+    }
+
+    /// Path for a new project, fails if the name is invalid or already taken
+    fn new_project_path(&self, name: &str) -> super::Result<PathBuf> {
+        let path = self.project_path(name)?;
+
+        if fs::symlink_metadata(&path).is_ok() {
+            return Err(ProjectServiceError::ProjectAlreadyExists(name.to_string()));
+        }
+
+        Ok(path)
+    }
+
+    /// Writes `content` to an already resolved path and creates missing parent dirs.
+    /// The content goes to a temporary file first which then replaces the file, so a crash
+    /// or a full disk never leaves a half written file behind
+    fn write_file(path: &Path, content: &[u8]) -> super::Result<()> {
+        static TEMP_FILES: AtomicU64 = AtomicU64::new(0);
+
+        // write through symlinks instead of replacing them
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+            return Err(ProjectServiceError::InvalidFilePath(format!("{:?}", path)));
+        };
+
+        create_dir_all(dir)
+            .map_err(|err| io_error(dir, err, ProjectServiceError::FailedToWriteFile))?;
+
+        let temp = dir.join(format!(
+            ".{}.{}-{}.tmp",
+            name.to_string_lossy(),
+            process::id(),
+            TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let write = || -> std::io::Result<()> {
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&temp)?;
+
+            if let Ok(original) = fs::metadata(&path) {
+                file.set_permissions(original.permissions())?;
+                // only privileged users can hand files to others, the content matters more
+                let _ = fchown(&file, Some(original.uid()), Some(original.gid()));
+            }
+
+            file.write_all(content)?;
+            file.sync_all()?;
+            fs::rename(&temp, &path)?;
+
+            // persist the rename itself
+            File::open(dir)?.sync_all()
+        };
+
+        write().map_err(|err| {
+            let _ = remove_file(&temp);
+            io_error(&path, err, ProjectServiceError::FailedToWriteFile)
+        })
+    }
+
+    /// Writes a text file without checking the permissions of the root, for files the
+    /// service creates itself
+    fn write_text(&self, project: &ProjectInfo, file: &str, content: &str) -> super::Result<()> {
+        let _guard = self.write_lock.lock().unwrap();
+
+        let path = Self::resolve_path(project, file)?;
+        Self::write_file(&path, content.as_bytes())
+    }
+
+    fn tree_entries(root: &Path, dir: &Path, prefix: &Path) -> super::Result<Vec<FileTreeEntry>> {
+        let entries = fs::read_dir(dir)
+            .map_err(|err| io_error(dir, err, ProjectServiceError::FailedToReadDir))?;
+
+        let mut tree = Vec::new();
+        for path in entries
             .filter_map(|entry| entry.ok())
             .map(|entry| entry.path())
-            .filter(|path| path.is_dir())
-            .map(|path| ProjectInfo {
-                name: path.file_name().unwrap().to_str().unwrap().to_string(),
-                dir: path,
-            })
             .sorted()
-            .collect();
+        {
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                warn!("leaving out {:?}, its name is not valid UTF-8", path);
+                continue;
+            };
+            let name = name.to_string();
+            let relative = prefix.join(&name);
+
+            if relative == Path::new(SETTINGS_FILE) || relative == Path::new(SECRETS_DIR) {
+                continue;
+            }
+
+            if path.is_symlink() && !is_inside(root, &path) {
+                continue;
+            }
+
+            // symlinked directories are not followed to avoid loops
+            let (kind, children) = if path.is_dir() && !path.is_symlink() {
+                // e.g. the data dir of a database container owned by another user
+                let children = Self::tree_entries(root, &path, &relative).unwrap_or_else(|err| {
+                    warn!("leaving out the content of {:?} - {}", path, err);
+                    Vec::new()
+                });
+                (FileType::Directory, children)
+            } else if path.is_file() {
+                (FileType::File, Vec::new())
+            } else {
+                continue;
+            };
+
+            tree.push(FileTreeEntry {
+                name,
+                path: relative.to_string_lossy().to_string(),
+                binary: kind == FileType::File && is_binary_file(&path),
+                kind,
+                children,
+            });
+        }
+
+        Ok(tree)
+    }
+}
+
+/// Checks only the start of the file, so listing a tree doesn't read every file completely
+fn is_binary_file(path: &Path) -> bool {
+    let mut start = Vec::with_capacity(8 * 1024);
+    let read = File::open(path).and_then(|file| file.take(8 * 1024).read_to_end(&mut start));
+
+    read.is_ok() && is_binary(&start)
+}
+
+/// Maps an io error to the variant describing its cause, `fallback` covers all unexpected causes
+pub(crate) fn io_error(
+    path: &Path,
+    err: io::Error,
+    fallback: fn(String, ErrorKind) -> ProjectServiceError,
+) -> ProjectServiceError {
+    error!("{:?} - {}", path, err);
+
+    let path = format!("{:?}", path);
+    match err.kind() {
+        ErrorKind::PermissionDenied => ProjectServiceError::PermissionDenied(path),
+        ErrorKind::StorageFull | ErrorKind::QuotaExceeded => ProjectServiceError::DiskFull(path),
+        ErrorKind::IsADirectory => ProjectServiceError::NotAFile(path),
+        ErrorKind::NotADirectory => ProjectServiceError::NotADirectory(path),
+        ErrorKind::InvalidFilename => ProjectServiceError::InvalidName(path),
+        kind => fallback(path, kind),
+    }
+}
+
+/// Copies the content of `from` into the existing dir `to`, symlinks are copied as links.
+/// The settings of the project are left out, so a copy starts without schedules or dependencies,
+/// as are the decrypted secrets
+fn copy_dir(from: &Path, to: &Path, is_root: bool) -> super::Result<()> {
+    let entries = fs::read_dir(from)
+        .map_err(|err| io_error(from, err, ProjectServiceError::FailedToReadDir))?;
+
+    for entry in entries {
+        let entry =
+            entry.map_err(|err| io_error(from, err, ProjectServiceError::FailedToReadDir))?;
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+
+        if is_root && (entry.file_name() == SETTINGS_FILE || entry.file_name() == SECRETS_DIR) {
+            continue;
+        }
+
+        let file_type = entry
+            .file_type()
+            .map_err(|err| io_error(&source, err, ProjectServiceError::FailedToReadFile))?;
+
+        if file_type.is_symlink() {
+            let link = fs::read_link(&source)
+                .map_err(|err| io_error(&source, err, ProjectServiceError::FailedToReadFile))?;
+            symlink(link, &target)
+                .map_err(|err| io_error(&target, err, ProjectServiceError::FailedToWriteFile))?;
+        } else if file_type.is_dir() {
+            create_dir(&target)
+                .map_err(|err| io_error(&target, err, ProjectServiceError::FailedToWriteFile))?;
+            copy_dir(&source, &target, false)?;
+        } else {
+            fs::copy(&source, &target)
+                .map_err(|err| io_error(&target, err, ProjectServiceError::FailedToWriteFile))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Renames the dir, across file systems - e.g. into a root on another disk - it is copied
+/// and the source removed afterwards
+fn move_dir(from: &Path, to: &Path) -> super::Result<()> {
+    match fs::rename(from, to) {
+        Err(err) if err.kind() == ErrorKind::CrossesDevices => {}
+        result => {
+            return result
+                .map_err(|err| io_error(from, err, ProjectServiceError::FailedToWriteFile));
+        }
+    }
+
+    create_dir(to).map_err(|err| io_error(to, err, ProjectServiceError::FailedToWriteFile))?;
+    if let Err(err) = copy_dir(from, to, false) {
+        let _ = remove_dir_all(to);
+        return Err(err);
+    }
+
+    remove_dir_all(from).map_err(|err| io_error(from, err, ProjectServiceError::FailedToDeleteFile))
+}
+
+/// Whether two settings only differ in what is recorded about running the project, which
+/// is kept up to date in read-only roots as well
+fn only_records_runs(current: &ProjectSettings, settings: &ProjectSettings) -> bool {
+    let configuration = |settings: &ProjectSettings| {
+        let mut settings = settings.clone();
+        settings.desired_state = None;
+        for schedule in settings.schedules.iter_mut() {
+            schedule.last_run = None;
+        }
+        settings
+    };
+
+    configuration(current) == configuration(settings)
+}
+
+/// Whether `path` resolves to a location inside of `root` once all symlinks are followed
+fn is_inside(root: &Path, path: &Path) -> bool {
+    match (root.canonicalize(), path.canonicalize()) {
+        (Ok(root), Ok(path)) => path.starts_with(root),
+        _ => false,
+    }
+}
+
+impl ProjectServiceTrait for ProjectService {
+    fn discover(&self) -> super::Result<Discovery> {
+        let mut discovery = Discovery::default();
+
+        for root in &self.roots {
+            let base_path = &root.root.path;
+            let dir = match fs::read_dir(base_path) {
+                Ok(dir) => dir,
+                Err(err) if root.is_default() => {
+                    return Err(io_error(
+                        base_path,
+                        err,
+                        ProjectServiceError::FailedToReadDir,
+                    ));
+                }
+                // a missing mount shouldn't hide the projects of all other roots
+                Err(err) => {
+                    warn!(
+                        "skipping root {} at {:?} - {}",
+                        root.root.name, base_path, err
+                    );
+                    continue;
+                }
+            };
 
-        Ok(projects)
+            let ignore_rules = IgnoreRules::read(base_path);
+            let dirs = dir
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir() && !path.ends_with(TRASH_DIR));
+
+            for path in dirs {
+                let name = path.file_name().unwrap().to_str().unwrap();
+
+                let reason = if !is_inside(base_path, &path) {
+                    Some(SkipReason::OutsideRoot)
+                } else if let Some(pattern) = ignore_rules.ignoring(name) {
+                    Some(SkipReason::Ignored {
+                        pattern: pattern.to_string(),
+                    })
+                } else if compose_file(&path).is_none() {
+                    Some(SkipReason::NoComposeFile)
+                } else {
+                    None
+                };
+
+                match reason {
+                    Some(reason) => discovery.skipped.push(SkippedDir {
+                        root: root.root.name.to_string(),
+                        dir: path,
+                        reason,
+                    }),
+                    None => discovery.projects.push(ProjectInfo {
+                        name: root.project_name(name),
+                        dir: path,
+                    }),
+                }
+            }
+        }
+
+        discovery.projects.sort();
+        discovery
+            .skipped
+            .sort_by(|a, b| (&a.root, &a.dir).cmp(&(&b.root, &b.dir)));
+
+        Ok(discovery)
     }
 
     fn project(&self, name: &str) -> super::Result<ProjectInfo> {
-        let path = self.base_path.join(name);
+        let path = self.project_path(name)?;
 
         let exist = fs::exists(&path)
-            .inspect_err(|err| error!("{}", err))
-            .map_err(|_| ProjectServiceError::FailedToReadDir(format!("{:?}", path)))?;
+            .map_err(|err| io_error(&path, err, ProjectServiceError::FailedToReadDir))?;
 
         if !exist {
             return Err(ProjectServiceError::ProjectNotFound(name.to_string()));
@@ -67,12 +544,8 @@ impl ProjectServiceTrait for ProjectService {
     }
 
     fn create(&self, name: &str) -> super::Result<ProjectInfo> {
-        let path = Self::save_file_path(name)?;
-        let path = self.base_path.join(path);
-
-        if path.exists() {
-            return Err(ProjectServiceError::ProjectAlreadyExists(name.to_string()));
-        }
+        self.check_permission(name, RootAction::Create)?;
+        let path = self.new_project_path(name)?;
 
         let _ = create_dir(&path);
 
@@ -81,50 +554,210 @@ impl ProjectServiceTrait for ProjectService {
             dir: path,
         };
 
-        self.update_file(&project_info, "compose.yml", "")?;
+        self.write_text(&project_info, "compose.yml", "")?;
+
+        Ok(project_info)
+    }
+
+    fn export(
+        &self,
+        project: &ProjectInfo,
+        exclude_env: bool,
+        writer: &mut dyn Write,
+    ) -> super::Result<()> {
+        archive::export(&project.dir, exclude_env, writer)
+    }
+
+    fn import(
+        &self,
+        name: &str,
+        conflict: NameConflict,
+        archive: &mut dyn Read,
+    ) -> super::Result<ProjectInfo> {
+        self.check_permission(name, RootAction::Create)?;
+
+        let mut new_name = name.to_string();
+        let mut path = self.new_project_path(name);
+
+        if conflict == NameConflict::Rename {
+            for number in 2.. {
+                let Err(ProjectServiceError::ProjectAlreadyExists(_)) = path else {
+                    break;
+                };
+                new_name = format!("{}-{}", name, number);
+                path = self.new_project_path(&new_name);
+            }
+        }
+
+        let path = path?;
+        create_dir(&path)
+            .map_err(|err| io_error(&path, err, ProjectServiceError::FailedToWriteFile))?;
+
+        if let Err(err) = archive::unpack(archive, &path, self.max_file_size) {
+            let _ = remove_dir_all(&path);
+            return Err(err);
+        }
+
+        Ok(ProjectInfo {
+            name: new_name,
+            dir: path,
+        })
+    }
+
+    fn templates(&self) -> super::Result<Vec<Template>> {
+        let mut templates = builtin_templates();
+
+        let Some(template_dir) = &self.template_dir else {
+            return Ok(templates);
+        };
+
+        let dirs = fs::read_dir(template_dir)
+            .map_err(|err| io_error(template_dir, err, ProjectServiceError::FailedToReadDir))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .sorted();
+
+        for dir in dirs {
+            let template = read_template(&dir)?;
+            templates.retain(|builtin| builtin.name != template.name);
+            templates.push(template);
+        }
+
+        Ok(templates)
+    }
+
+    fn create_from_template(
+        &self,
+        name: &str,
+        template: &str,
+        variables: &HashMap<String, String>,
+    ) -> super::Result<ProjectInfo> {
+        let files = self
+            .templates()?
+            .into_iter()
+            .find(|candidate| candidate.name == template)
+            .ok_or_else(|| ProjectServiceError::TemplateNotFound(template.to_string()))?
+            .render(variables)?;
+
+        let project_info = self.create(name)?;
+
+        for (file, content) in files {
+            if let Err(err) = self.write_text(&project_info, &file, &content) {
+                let _ = remove_dir_all(&project_info.dir);
+                return Err(err);
+            }
+        }
 
         Ok(project_info)
     }
 
     fn delete(&self, project: &ProjectInfo) -> super::Result<()> {
+        self.check_permission(&project.name, RootAction::Delete)?;
+
         if !project.dir.exists() {
             return Err(ProjectServiceError::ProjectNotFound(
                 project.name.to_string(),
             ));
         }
 
-        remove_dir_all(&project.dir)
-            .inspect_err(|err| error!("{}", err))
-            .map_err(|_| {
-                ProjectServiceError::FailedToDeleteProject(format!("{:?}", project.dir))
+        let (root, _) = self.split_name(&project.name);
+        root.trash
+            .put(&project.dir, &project.name, None, FileType::Directory)
+            .map_err(|err| match err {
+                ProjectServiceError::FailedToDeleteFile(path, kind) => {
+                    ProjectServiceError::FailedToDeleteProject(path, kind)
+                }
+                err => err,
             })?;
 
         Ok(())
     }
 
+    /// Moves the project into another root if `new_name` names one
+    fn rename(&self, project: &ProjectInfo, new_name: &str) -> super::Result<ProjectInfo> {
+        self.check_permission(&project.name, RootAction::Delete)?;
+        self.check_permission(new_name, RootAction::Create)?;
+        let path = self.new_project_path(new_name)?;
+
+        move_dir(&project.dir, &path)?;
+
+        let renamed = ProjectInfo {
+            name: new_name.to_string(),
+            dir: path,
+        };
+
+        // keep dependencies of other projects pointing to the renamed project
+        for other in self.all_projects()? {
+            let updated = self.modify_settings(&other, &mut |settings| {
+                for dependency in settings.dependencies.iter_mut() {
+                    if *dependency == project.name {
+                        *dependency = new_name.to_string();
+                    }
+                }
+                Ok(())
+            });
+
+            // the project is renamed already, a read-only root only keeps the old name
+            if let Err(err) = updated {
+                warn!(
+                    "failed to update the dependencies of {} - {}",
+                    other.name, err
+                );
+            }
+        }
+
+        Ok(renamed)
+    }
+
+    fn duplicate(&self, project: &ProjectInfo, new_name: &str) -> super::Result<ProjectInfo> {
+        self.check_permission(new_name, RootAction::Create)?;
+        let path = self.new_project_path(new_name)?;
+
+        create_dir(&path)
+            .map_err(|err| io_error(&path, err, ProjectServiceError::FailedToWriteFile))?;
+
+        if let Err(err) = copy_dir(&project.dir, &path, true) {
+            let _ = remove_dir_all(&path);
+            return Err(err);
+        }
+
+        Ok(ProjectInfo {
+            name: new_name.to_string(),
+            dir: path,
+        })
+    }
+
     fn files(&self, project: &ProjectInfo) -> super::Result<Vec<String>> {
-        let dir = fs::read_dir(&project.dir)
-            .inspect_err(|err| error!("{}", err))
-            .map_err(|_| ProjectServiceError::FailedToReadDir(format!("{:?}", project.dir)))?;
+        fn collect(entries: Vec<FileTreeEntry>, files: &mut Vec<String>) {
+            for entry in entries {
+                match entry.kind {
+                    FileType::File => files.push(entry.path),
+                    FileType::Directory => collect(entry.children, files),
+                }
+            }
+        }
 
-        let files = dir
-            .filter_map(|entry| entry.ok())
-            .map(|entry| entry.path())
-            .filter(|path| path.is_file())
-            .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
-            .sorted()
-            .collect();
+        let mut files = Vec::new();
+        collect(self.tree(project)?, &mut files);
+
+        Ok(files.into_iter().sorted().collect())
+    }
+
+    fn tree(&self, project: &ProjectInfo) -> super::Result<Vec<FileTreeEntry>> {
+        let root = project
+            .dir
+            .canonicalize()
+            .map_err(|_| ProjectServiceError::ProjectNotFound(project.name.to_string()))?;
 
-        Ok(files)
+        Self::tree_entries(&root, &root, Path::new(""))
     }
 
     fn read_file(&self, project: &ProjectInfo, file: &str) -> super::Result<String> {
-        let path = Self::save_file_path(file)?;
-        let path = project.dir.join(path);
+        let path = Self::resolve_path(project, file)?;
 
         let exist = fs::exists(&path)
-            .inspect_err(|err| error!("{}", err))
-            .map_err(|_| ProjectServiceError::FailedToReadDir(format!("{:?}", path)))?;
+            .map_err(|err| io_error(&path, err, ProjectServiceError::FailedToReadDir))?;
 
         if !exist {
             return Err(ProjectServiceError::FileNotFound {
@@ -133,11 +766,86 @@ impl ProjectServiceTrait for ProjectService {
             });
         }
 
-        let content = fs::read_to_string(&path)
-            .inspect_err(|err| error!("{}", err))
-            .map_err(|_| ProjectServiceError::FailedToReadDir(format!("{:?}", path)))?;
+        if path.is_dir() {
+            return Err(ProjectServiceError::NotAFile(file.to_string()));
+        }
+
+        let content = fs::read(&path)
+            .map_err(|err| io_error(&path, err, ProjectServiceError::FailedToReadFile))?;
+
+        String::from_utf8(content).map_err(|_| ProjectServiceError::BinaryFile(file.to_string()))
+    }
 
-        Ok(content)
+    fn file_path(&self, project: &ProjectInfo, file: &str) -> super::Result<PathBuf> {
+        let path = Self::resolve_path(project, file)?;
+
+        if path.is_dir() {
+            return Err(ProjectServiceError::NotAFile(file.to_string()));
+        }
+
+        if !path.is_file() {
+            return Err(ProjectServiceError::FileNotFound {
+                project: project.name.to_string(),
+                file: file.to_string(),
+            });
+        }
+
+        Ok(path)
+    }
+
+    fn file_metadata(&self, project: &ProjectInfo, file: &str) -> super::Result<FileMetadata> {
+        let path = self.file_path(project, file)?;
+        let to_error = |err| io_error(&path, err, ProjectServiceError::FailedToReadFile);
+
+        let metadata = fs::metadata(&path).map_err(to_error)?;
+        // larger files can't be edited anyway, hashing them would read e.g. volume data completely
+        let hashed = metadata.len() <= self.max_file_size as u64;
+        let content = if hashed {
+            fs::read(&path).map_err(to_error)?
+        } else {
+            let mut start = Vec::with_capacity(8 * 1024);
+            File::open(&path)
+                .and_then(|file| file.take(8 * 1024).read_to_end(&mut start))
+                .map_err(to_error)?;
+            start
+        };
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+
+        Ok(FileMetadata {
+            path: file.to_string(),
+            size: metadata.len(),
+            modified: metadata.modified().map_err(to_error)?.into(),
+            mode: format!("{:o}", metadata.permissions().mode() & 0o7777),
+            format: FileFormat::detect(name, &content),
+            hash: hashed.then(|| revision(&content)),
+        })
+    }
+
+    fn write_bytes(&self, project: &ProjectInfo, file: &str, content: &[u8]) -> super::Result<()> {
+        self.check_permission(&project.name, RootAction::Edit)?;
+
+        if content.len() > self.max_file_size {
+            return Err(ProjectServiceError::FileTooLarge {
+                file: file.to_string(),
+                max_size: self.max_file_size,
+            });
+        }
+
+        let _guard = self.write_lock.lock().unwrap();
+
+        let path = Self::resolve_path(project, file)?;
+        Self::write_file(&path, content)
+    }
+
+    fn max_file_size(&self) -> usize {
+        self.max_file_size
+    }
+
+    fn redaction_policy(&self) -> &RedactionPolicy {
+        &self.redaction_policy
     }
 
     fn update_file(
@@ -146,23 +854,46 @@ impl ProjectServiceTrait for ProjectService {
         file: &str,
         content: &str,
     ) -> super::Result<String> {
-        let path = Self::save_file_path(file)?;
-        let path = project.dir.join(path);
+        self.check_permission(&project.name, RootAction::Edit)?;
+        self.write_text(project, file, content)?;
+
+        Ok(content.to_string())
+    }
+
+    fn update_file_if_match(
+        &self,
+        project: &ProjectInfo,
+        file: &str,
+        content: &str,
+        revisions: &[String],
+    ) -> super::Result<String> {
+        self.check_permission(&project.name, RootAction::Edit)?;
+        let _guard = self.write_lock.lock().unwrap();
 
-        let mut file = File::create(&path)
-            .inspect_err(|err| error!("{}", err))
-            .map_err(|_| ProjectServiceError::FailedToReadDir(format!("{:?}", path)))?;
+        let path = Self::resolve_path(project, file)?;
+        let current = fs::read(&path).ok().filter(|_| path.is_file());
+        let current_revision = current.as_deref().map(revision);
 
-        file.write_all(content.as_bytes())
-            .inspect_err(|err| error!("{}", err))
-            .map_err(|_| ProjectServiceError::FailedToReadDir(format!("{:?}", path)))?;
+        let matches = revisions.iter().any(|expected| {
+            (expected == "*" && current.is_some()) || Some(expected) == current_revision.as_ref()
+        });
+
+        if !matches {
+            return Err(ProjectServiceError::FileChanged {
+                file: file.to_string(),
+                revision: current_revision,
+                content: current.and_then(|content| String::from_utf8(content).ok()),
+            });
+        }
+
+        Self::write_file(&path, content.as_bytes())?;
 
         Ok(content.to_string())
     }
 
     fn delete_file(&self, project: &ProjectInfo, file: &str) -> super::Result<()> {
-        let path = Self::save_file_path(file)?;
-        let path = project.dir.join(path);
+        self.check_permission(&project.name, RootAction::Delete)?;
+        let path = Self::resolve_path(project, file)?;
 
         if !path.exists() {
             return Err(ProjectServiceError::FileNotFound {
@@ -171,10 +902,260 @@ impl ProjectServiceTrait for ProjectService {
             });
         }
 
-        remove_file(&path)
-            .inspect_err(|err| error!("{}", err))
-            .map_err(|_| ProjectServiceError::FailedToReadDir(format!("{:?}", path)))?;
+        if path.is_dir() {
+            return Err(ProjectServiceError::NotAFile(file.to_string()));
+        }
+
+        let (root, _) = self.split_name(&project.name);
+        root.trash
+            .put(&path, &project.name, Some(file), FileType::File)?;
 
         Ok(())
     }
+
+    fn create_dir(&self, project: &ProjectInfo, dir: &str) -> super::Result<()> {
+        self.check_permission(&project.name, RootAction::Edit)?;
+        let path = Self::resolve_path(project, dir)?;
+
+        create_dir_all(&path)
+            .map_err(|err| io_error(&path, err, ProjectServiceError::FailedToWriteFile))?;
+
+        Ok(())
+    }
+
+    fn delete_dir(&self, project: &ProjectInfo, dir: &str) -> super::Result<()> {
+        self.check_permission(&project.name, RootAction::Delete)?;
+        let path = Self::resolve_path(project, dir)?;
+
+        if path.is_file() {
+            return Err(ProjectServiceError::NotADirectory(dir.to_string()));
+        }
+
+        if !path.is_dir() {
+            return Err(ProjectServiceError::FileNotFound {
+                project: project.name.to_string(),
+                file: dir.to_string(),
+            });
+        }
+
+        let (root, _) = self.split_name(&project.name);
+        root.trash
+            .put(&path, &project.name, Some(dir), FileType::Directory)?;
+
+        Ok(())
+    }
+
+    fn roots(&self) -> Vec<ProjectRoot> {
+        self.roots.iter().map(|root| root.root.clone()).collect()
+    }
+
+    fn check_permission(&self, name: &str, action: RootAction) -> super::Result<()> {
+        let (root, _) = self.split_name(name);
+
+        if !root.root.permissions.allows(action) {
+            return Err(ProjectServiceError::RootPermissionDenied {
+                root: root.root.name.to_string(),
+                action,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn trash(&self) -> super::Result<Vec<TrashEntry>> {
+        let mut entries = Vec::new();
+        for root in &self.roots {
+            entries.extend(root.trash.entries()?);
+        }
+
+        Ok(entries
+            .into_iter()
+            .sorted_by(|a, b| b.deleted.cmp(&a.deleted).then(b.id.cmp(&a.id)))
+            .collect())
+    }
+
+    fn restore(&self, id: &str) -> super::Result<TrashEntry> {
+        let trash = self.trash_of(id)?;
+        let entry = trash.entry(id)?;
+
+        let action = match entry.file {
+            None => RootAction::Create,
+            Some(_) => RootAction::Edit,
+        };
+        self.check_permission(&entry.project, action)?;
+
+        let target = match &entry.file {
+            None => self.new_project_path(&entry.project)?,
+            Some(file) => {
+                let project = self.project(&entry.project)?;
+                let target = Self::resolve_path(&project, file)?;
+
+                if fs::symlink_metadata(&target).is_ok() {
+                    return Err(ProjectServiceError::FileAlreadyExists {
+                        project: entry.project.to_string(),
+                        file: file.to_string(),
+                    });
+                }
+
+                if let Some(dir) = target.parent() {
+                    create_dir_all(dir).map_err(|err| {
+                        io_error(dir, err, ProjectServiceError::FailedToWriteFile)
+                    })?;
+                }
+
+                target
+            }
+        };
+
+        trash.restore(id, &target)
+    }
+
+    fn purge(&self, id: &str) -> super::Result<TrashEntry> {
+        self.trash_of(id)?.purge(id)
+    }
+
+    fn purge_expired(&self) -> super::Result<Vec<TrashEntry>> {
+        let Some(retention) = self.trash_retention else {
+            return Ok(Vec::new());
+        };
+
+        let before = Local::now() - retention;
+        let mut purged = Vec::new();
+        for root in &self.roots {
+            purged.extend(root.trash.purge_before(&before)?);
+        }
+
+        Ok(purged)
+    }
+
+    fn settings(&self, project: &ProjectInfo) -> super::Result<ProjectSettings> {
+        let path = project.dir.join(SETTINGS_FILE);
+
+        if !path.exists() {
+            return Ok(ProjectSettings::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|err| io_error(&path, err, ProjectServiceError::FailedToReadFile))?;
+
+        serde_json::from_str(&content).map_err(|err| ProjectServiceError::InvalidSettings {
+            project: project.name.to_string(),
+            error: err.to_string(),
+        })
+    }
+
+    fn modify_settings(
+        &self,
+        project: &ProjectInfo,
+        modify: &mut dyn FnMut(&mut ProjectSettings) -> super::Result<()>,
+    ) -> super::Result<ProjectSettings> {
+        let _guard = self.write_lock.lock().unwrap();
+
+        let current = self.settings(project)?;
+        let mut settings = current.clone();
+        modify(&mut settings)?;
+
+        if !only_records_runs(&current, &settings) {
+            self.check_permission(&project.name, RootAction::Edit)?;
+        }
+
+        let path = project.dir.join(SETTINGS_FILE);
+        if settings == current && path.exists() {
+            return Ok(settings);
+        }
+
+        let content = serde_json::to_string_pretty(&settings).map_err(|err| {
+            ProjectServiceError::InvalidSettings {
+                project: project.name.to_string(),
+                error: err.to_string(),
+            }
+        })?;
+
+        Self::write_file(&path, content.as_bytes())?;
+
+        Ok(settings)
+    }
+
+    fn secrets(&self, project: &ProjectInfo) -> super::Result<Vec<SecretInfo>> {
+        Ok(self
+            .settings(project)?
+            .secrets
+            .into_iter()
+            .map(|(name, secret)| SecretInfo {
+                name,
+                updated: secret.updated,
+            })
+            .collect())
+    }
+
+    fn set_secret(
+        &self,
+        project: &ProjectInfo,
+        name: &str,
+        value: &str,
+    ) -> super::Result<SecretInfo> {
+        let key = self
+            .secrets_key
+            .as_ref()
+            .ok_or(ProjectServiceError::SecretsDisabled)?;
+
+        if !secrets::is_valid_name(name) {
+            return Err(ProjectServiceError::InvalidSecretName(name.to_string()));
+        }
+
+        let secret = Secret {
+            value: key.encrypt(name, value)?,
+            updated: Local::now(),
+        };
+
+        self.modify_settings(project, &mut |settings| {
+            settings.secrets.insert(name.to_string(), secret.clone());
+            Ok(())
+        })?;
+
+        Ok(SecretInfo {
+            name: name.to_string(),
+            updated: secret.updated,
+        })
+    }
+
+    fn delete_secret(&self, project: &ProjectInfo, name: &str) -> super::Result<()> {
+        self.modify_settings(project, &mut |settings| {
+            if settings.secrets.remove(name).is_none() {
+                return Err(ProjectServiceError::SecretNotFound {
+                    project: project.name.to_string(),
+                    name: name.to_string(),
+                });
+            }
+
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    fn materialize_secrets(&self, project: &ProjectInfo) -> super::Result<()> {
+        let settings = self.settings(project)?;
+
+        if settings.secrets.is_empty() {
+            return secrets::clear(&project.dir);
+        }
+
+        let key = self
+            .secrets_key
+            .as_ref()
+            .ok_or(ProjectServiceError::SecretsDisabled)?;
+
+        let decrypted = settings
+            .secrets
+            .iter()
+            .map(|(name, secret)| Ok((name.to_string(), key.decrypt(name, &secret.value)?)))
+            .collect::<super::Result<Vec<_>>>()?;
+
+        secrets::materialize(&project.dir, &decrypted)
+    }
+
+    fn clear_secrets(&self, project: &ProjectInfo) -> super::Result<()> {
+        secrets::clear(&project.dir)
+    }
 }
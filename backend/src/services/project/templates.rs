@@ -0,0 +1,212 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{ProjectServiceError, Result};
+
+/// Describes a user defined template, it lies next to the files of the template
+pub const TEMPLATE_FILE: &str = "template.json";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct Template {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub parameters: Vec<TemplateParameter>,
+    /// content of every file by its path, `{{ parameter }}` placeholders are not rendered yet
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TemplateParameter {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// used if no value was passed, a parameter without default is required
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+impl Template {
+    /// Replaces all placeholders in every file with the passed variables or the parameter defaults
+    pub fn render(&self, variables: &HashMap<String, String>) -> Result<BTreeMap<String, String>> {
+        self.files
+            .iter()
+            .map(|(path, content)| Ok((path.to_string(), self.render_content(content, variables)?)))
+            .collect()
+    }
+
+    fn render_content(&self, content: &str, variables: &HashMap<String, String>) -> Result<String> {
+        let mut rendered = String::with_capacity(content.len());
+        let mut rest = content;
+
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                break;
+            };
+
+            let name = rest[start + 2..start + end].trim();
+            rendered.push_str(&rest[..start]);
+            rendered.push_str(self.value(name, variables)?);
+            rest = &rest[start + end + 2..];
+        }
+
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
+
+    fn value<'a>(&'a self, name: &str, variables: &'a HashMap<String, String>) -> Result<&'a str> {
+        let default = self
+            .parameters
+            .iter()
+            .find(|parameter| parameter.name == name)
+            .and_then(|parameter| parameter.default.as_deref());
+
+        variables
+            .get(name)
+            .map(String::as_str)
+            .or(default)
+            .ok_or_else(|| ProjectServiceError::MissingTemplateParameter {
+                template: self.name.to_string(),
+                parameter: name.to_string(),
+            })
+    }
+}
+
+/// Templates shipped with ContainerYard
+pub fn builtin_templates() -> Vec<Template> {
+    vec![
+        builtin(
+            "postgres",
+            "PostgreSQL database with a persistent volume",
+            &[
+                ("POSTGRES_VERSION", "Image tag", Some("17")),
+                ("POSTGRES_USER", "Name of the superuser", Some("postgres")),
+                ("POSTGRES_PASSWORD", "Password of the superuser", None),
+                (
+                    "POSTGRES_DB",
+                    "Database created on first start",
+                    Some("postgres"),
+                ),
+                ("PORT", "Port on the host", Some("5432")),
+            ],
+            &[
+                (
+                    "compose.yml",
+                    "services:\n  postgres:\n    image: postgres:{{ POSTGRES_VERSION }}\n    restart: unless-stopped\n    env_file: .env\n    ports:\n      - \"{{ PORT }}:5432\"\n    volumes:\n      - data:/var/lib/postgresql/data\n\nvolumes:\n  data:\n",
+                ),
+                (
+                    ".env",
+                    "POSTGRES_USER={{ POSTGRES_USER }}\nPOSTGRES_PASSWORD={{ POSTGRES_PASSWORD }}\nPOSTGRES_DB={{ POSTGRES_DB }}\n",
+                ),
+            ],
+        ),
+        builtin(
+            "redis",
+            "Redis cache with append only persistence",
+            &[
+                ("REDIS_VERSION", "Image tag", Some("7")),
+                ("PORT", "Port on the host", Some("6379")),
+            ],
+            &[(
+                "compose.yml",
+                "services:\n  redis:\n    image: redis:{{ REDIS_VERSION }}\n    restart: unless-stopped\n    command: redis-server --appendonly yes\n    ports:\n      - \"{{ PORT }}:6379\"\n    volumes:\n      - data:/data\n\nvolumes:\n  data:\n",
+            )],
+        ),
+        builtin(
+            "nginx",
+            "Nginx serving static files from the html directory",
+            &[
+                ("NGINX_VERSION", "Image tag", Some("stable")),
+                ("PORT", "Port on the host", Some("8080")),
+            ],
+            &[
+                (
+                    "compose.yml",
+                    "services:\n  nginx:\n    image: nginx:{{ NGINX_VERSION }}\n    restart: unless-stopped\n    ports:\n      - \"{{ PORT }}:80\"\n    volumes:\n      - ./html:/usr/share/nginx/html:ro\n",
+                ),
+                (
+                    "html/index.html",
+                    "<!DOCTYPE html>\n<html>\n  <body>\n    <h1>It works!</h1>\n  </body>\n</html>\n",
+                ),
+            ],
+        ),
+    ]
+}
+
+fn builtin(
+    name: &str,
+    description: &str,
+    parameters: &[(&str, &str, Option<&str>)],
+    files: &[(&str, &str)],
+) -> Template {
+    Template {
+        name: name.to_string(),
+        description: description.to_string(),
+        parameters: parameters
+            .iter()
+            .map(|(name, description, default)| TemplateParameter {
+                name: name.to_string(),
+                description: description.to_string(),
+                default: default.map(str::to_string),
+            })
+            .collect(),
+        files: files
+            .iter()
+            .map(|(path, content)| (path.to_string(), content.to_string()))
+            .collect(),
+    }
+}
+
+/// Reads a user defined template from its directory, the dir name is the name of the template
+pub fn read_template(dir: &Path) -> Result<Template> {
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let invalid = |error: String| ProjectServiceError::InvalidTemplate {
+        template: name.to_string(),
+        error,
+    };
+
+    let mut template = match fs::read_to_string(dir.join(TEMPLATE_FILE)) {
+        Ok(content) => serde_json::from_str(&content).map_err(|err| invalid(err.to_string()))?,
+        Err(_) => Template::default(),
+    };
+    template.name = name.to_string();
+
+    collect_files(dir, "", &mut template.files).map_err(|err| invalid(err.to_string()))?;
+
+    Ok(template)
+}
+
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    files: &mut BTreeMap<String, String>,
+) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = format!("{}{}", prefix, name);
+
+        if prefix.is_empty() && name == TEMPLATE_FILE {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), &format!("{}/", path), files)?;
+        } else {
+            files.insert(path, fs::read_to_string(entry.path())?);
+        }
+    }
+
+    Ok(())
+}
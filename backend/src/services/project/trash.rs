@@ -0,0 +1,141 @@
+use std::{
+    fs::{self, create_dir_all, remove_dir_all},
+    path::{Component, Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use chrono::{DateTime, Local};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::{FileType, ProjectServiceError, Result, service::io_error};
+
+/// Directory next to the projects holding deleted projects and files
+pub const TRASH_DIR: &str = ".containeryard-trash";
+
+/// Describes a trashed item, it lies next to the item inside the dir of the entry
+const ENTRY_FILE: &str = "entry.json";
+const ITEM: &str = "item";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TrashEntry {
+    pub id: String,
+    pub project: String,
+    /// path inside the project, `None` if the whole project was deleted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: FileType,
+    pub deleted: DateTime<Local>,
+}
+
+pub struct Trash {
+    dir: PathBuf,
+}
+
+impl Trash {
+    pub fn new(dir: PathBuf) -> Trash {
+        Trash { dir }
+    }
+
+    /// Moves the file or directory at `path` into the trash
+    pub fn put(
+        &self,
+        path: &Path,
+        project: &str,
+        file: Option<&str>,
+        kind: FileType,
+    ) -> Result<TrashEntry> {
+        static ENTRIES: AtomicU64 = AtomicU64::new(0);
+
+        let deleted = Local::now();
+        let entry = TrashEntry {
+            id: format!(
+                "{}-{}",
+                deleted.format("%Y%m%d%H%M%S%3f"),
+                ENTRIES.fetch_add(1, Ordering::Relaxed)
+            ),
+            project: project.to_string(),
+            file: file.map(str::to_string),
+            kind,
+            deleted,
+        };
+
+        let entry_dir = self.dir.join(&entry.id);
+        create_dir_all(&entry_dir)
+            .map_err(|err| io_error(&entry_dir, err, ProjectServiceError::FailedToDeleteFile))?;
+
+        let content = serde_json::to_string_pretty(&entry).unwrap_or_default();
+        let moved = fs::write(entry_dir.join(ENTRY_FILE), content)
+            .and_then(|_| fs::rename(path, entry_dir.join(ITEM)));
+
+        if let Err(err) = moved {
+            let _ = remove_dir_all(&entry_dir);
+            return Err(io_error(path, err, ProjectServiceError::FailedToDeleteFile));
+        }
+
+        Ok(entry)
+    }
+
+    /// All entries, the most recently deleted first
+    pub fn entries(&self) -> Result<Vec<TrashEntry>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let dirs = fs::read_dir(&self.dir)
+            .map_err(|err| io_error(&self.dir, err, ProjectServiceError::FailedToReadDir))?;
+
+        let entries = dirs
+            .filter_map(|dir| dir.ok())
+            .filter_map(|dir| self.entry(&dir.file_name().to_string_lossy()).ok())
+            .sorted_by(|a, b| b.deleted.cmp(&a.deleted).then(b.id.cmp(&a.id)))
+            .collect();
+
+        Ok(entries)
+    }
+
+    pub fn entry(&self, id: &str) -> Result<TrashEntry> {
+        let not_found = || ProjectServiceError::TrashEntryNotFound(id.to_string());
+
+        let mut components = Path::new(id).components();
+        let (Some(Component::Normal(_)), None) = (components.next(), components.next()) else {
+            return Err(not_found());
+        };
+
+        let content =
+            fs::read_to_string(self.dir.join(id).join(ENTRY_FILE)).map_err(|_| not_found())?;
+        serde_json::from_str(&content).map_err(|_| not_found())
+    }
+
+    /// Moves the item of the entry back to `target` and removes the entry
+    pub fn restore(&self, id: &str, target: &Path) -> Result<TrashEntry> {
+        let entry = self.entry(id)?;
+        let entry_dir = self.dir.join(id);
+
+        fs::rename(entry_dir.join(ITEM), target)
+            .map_err(|err| io_error(target, err, ProjectServiceError::FailedToWriteFile))?;
+        let _ = remove_dir_all(&entry_dir);
+
+        Ok(entry)
+    }
+
+    pub fn purge(&self, id: &str) -> Result<TrashEntry> {
+        let entry = self.entry(id)?;
+        let entry_dir = self.dir.join(id);
+
+        remove_dir_all(&entry_dir)
+            .map_err(|err| io_error(&entry_dir, err, ProjectServiceError::FailedToDeleteFile))?;
+
+        Ok(entry)
+    }
+
+    /// Finally deletes every entry which was deleted before `time`
+    pub fn purge_before(&self, time: &DateTime<Local>) -> Result<Vec<TrashEntry>> {
+        self.entries()?
+            .into_iter()
+            .filter(|entry| entry.deleted < *time)
+            .map(|entry| self.purge(&entry.id))
+            .collect()
+    }
+}
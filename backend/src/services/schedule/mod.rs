@@ -0,0 +1,182 @@
+use axum::{Json, http::StatusCode, response::IntoResponse, response::Response};
+use chrono::{DateTime, Local};
+use croner::Cron;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use super::{
+    container::{self, ContainerServiceTrait},
+    notification::NotificationEvent,
+    project::{DesiredState, ProjectInfo, ProjectServiceError},
+};
+
+pub mod service;
+
+pub type Result<T> = core::result::Result<T, Error>;
+pub type Error = ScheduleServiceError;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ScheduleServiceError {
+    #[error("Invalid cron expression '{cron}' - {error}")]
+    InvalidCron { cron: String, error: String },
+
+    #[error("Could not find Schedule {id} for {project}")]
+    ScheduleNotFound { project: String, id: u32 },
+
+    #[error(transparent)]
+    Project(#[from] ProjectServiceError),
+}
+
+impl IntoResponse for ScheduleServiceError {
+    fn into_response(self) -> Response {
+        let (status, code, error) = match self {
+            ScheduleServiceError::Project(error) => return error.into_response(),
+            error @ ScheduleServiceError::InvalidCron { .. } => {
+                (StatusCode::BAD_REQUEST, "invalid_cron", error)
+            }
+            error @ ScheduleServiceError::ScheduleNotFound { .. } => {
+                (StatusCode::NOT_FOUND, "schedule_not_found", error)
+            }
+        };
+
+        let body = Json(json!({ "error": error.to_string(), "code": code }));
+        (status, body).into_response()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleAction {
+    Start,
+    Stop,
+    Restart,
+    Pull,
+    /// pull new images and recreate changed containers
+    Update,
+}
+
+impl ScheduleAction {
+    pub fn execute(
+        &self,
+        container_service: &dyn ContainerServiceTrait,
+        project: &ProjectInfo,
+    ) -> container::Result<()> {
+        match self {
+            ScheduleAction::Start => container_service.start(project),
+            ScheduleAction::Stop => container_service.stop(project),
+            ScheduleAction::Restart => container_service
+                .stop(project)
+                .and_then(|_| container_service.start(project)),
+            ScheduleAction::Pull => container_service.pull(project),
+            ScheduleAction::Update => container_service
+                .pull(project)
+                .and_then(|_| container_service.start(project)),
+        }
+    }
+
+    pub fn desired_state(&self) -> Option<DesiredState> {
+        match self {
+            ScheduleAction::Start | ScheduleAction::Restart | ScheduleAction::Update => {
+                Some(DesiredState::Running)
+            }
+            ScheduleAction::Stop => Some(DesiredState::Stopped),
+            ScheduleAction::Pull => None,
+        }
+    }
+
+    /// The event to notify about once the action succeeded
+    pub fn event(&self) -> Option<NotificationEvent> {
+        match self {
+            ScheduleAction::Start | ScheduleAction::Restart | ScheduleAction::Update => {
+                Some(NotificationEvent::Started)
+            }
+            ScheduleAction::Stop => Some(NotificationEvent::Stopped),
+            ScheduleAction::Pull => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ScheduleRun {
+    pub timestamp: DateTime<Local>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct NewSchedule {
+    pub cron: String,
+    pub action: ScheduleAction,
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Schedule {
+    pub id: u32,
+    pub cron: String,
+    pub action: ScheduleAction,
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub last_run: Option<ScheduleRun>,
+}
+
+impl Schedule {
+    /// Parses a cron expression with five fields or six if seconds are included
+    pub fn parse_cron(cron: &str) -> Result<Cron> {
+        Cron::new(cron)
+            .with_seconds_optional()
+            .parse()
+            .map_err(|err| ScheduleServiceError::InvalidCron {
+                cron: cron.to_string(),
+                error: err.to_string(),
+            })
+    }
+
+    pub fn next_run(&self, after: &DateTime<Local>) -> Option<DateTime<Local>> {
+        if !self.enabled {
+            return None;
+        }
+
+        Self::parse_cron(&self.cron)
+            .ok()?
+            .find_next_occurrence(after, false)
+            .ok()
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct ScheduleInfo {
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    pub next_run: Option<DateTime<Local>>,
+}
+
+impl ScheduleInfo {
+    pub fn new(schedule: Schedule, now: &DateTime<Local>) -> Self {
+        Self {
+            next_run: schedule.next_run(now),
+            schedule,
+        }
+    }
+}
+
+pub trait ScheduleServiceTrait: Send + Sync {
+    fn schedules(&self, project: &ProjectInfo) -> Result<Vec<ScheduleInfo>>;
+    fn add(&self, project: &ProjectInfo, schedule: &NewSchedule) -> Result<ScheduleInfo>;
+    fn update(
+        &self,
+        project: &ProjectInfo,
+        id: u32,
+        schedule: &NewSchedule,
+    ) -> Result<ScheduleInfo>;
+    fn remove(&self, project: &ProjectInfo, id: u32) -> Result<()>;
+    /// Executes every schedule which was due after `since` and up to `now`
+    fn run_due(&self, since: &DateTime<Local>, now: &DateTime<Local>);
+}
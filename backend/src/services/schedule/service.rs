@@ -0,0 +1,228 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Local};
+use itertools::Itertools;
+use tracing::error;
+
+use crate::services::{
+    container::ContainerServiceTrait,
+    notification::{NotificationEvent, NotificationServiceTrait},
+    project::{ProjectInfo, ProjectServiceTrait},
+};
+
+use super::{
+    NewSchedule, Schedule, ScheduleInfo, ScheduleRun, ScheduleServiceError, ScheduleServiceTrait,
+};
+
+pub struct ScheduleService {
+    project_service: Arc<dyn ProjectServiceTrait>,
+    container_service: Arc<dyn ContainerServiceTrait>,
+    notification_service: Arc<dyn NotificationServiceTrait>,
+}
+
+impl ScheduleService {
+    pub fn new(
+        project_service: Arc<dyn ProjectServiceTrait>,
+        container_service: Arc<dyn ContainerServiceTrait>,
+        notification_service: Arc<dyn NotificationServiceTrait>,
+    ) -> ScheduleService {
+        Self {
+            project_service,
+            container_service,
+            notification_service,
+        }
+    }
+
+    /// Checks for due schedules every `interval` until the task is dropped
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+        let mut since = Local::now();
+
+        loop {
+            interval.tick().await;
+
+            let now = Local::now();
+            tokio::task::block_in_place(|| self.run_due(&since, &now));
+            since = now;
+        }
+    }
+
+    fn run_schedule(&self, project: &ProjectInfo, schedule: &Schedule, now: &DateTime<Local>) {
+        if let Some(state) = schedule.action.desired_state()
+            && let Err(err) = self.project_service.set_desired_state(project, state)
+        {
+            error!("{}", err);
+        }
+
+        let result = schedule
+            .action
+            .execute(self.container_service.as_ref(), project);
+
+        match schedule.action.event() {
+            Some(event) => self
+                .notification_service
+                .notify_outcome(project, event, &result),
+            None if result.is_err() => self.notification_service.notify_outcome(
+                project,
+                NotificationEvent::CommandFailed,
+                &result,
+            ),
+            None => {}
+        }
+
+        let run = ScheduleRun {
+            timestamp: *now,
+            success: result.is_ok(),
+            error: result.err().map(|err| err.to_string()),
+        };
+
+        // the action may take a while - re-read the settings to not overwrite changes made in the meantime
+        let result = self
+            .project_service
+            .modify_settings(project, &mut |settings| {
+                if let Some(current) = settings
+                    .schedules
+                    .iter_mut()
+                    .find(|current| current.id == schedule.id)
+                {
+                    current.last_run = Some(run.clone());
+                }
+                Ok(())
+            });
+
+        if let Err(err) = result {
+            error!("{}", err);
+        }
+    }
+}
+
+impl ScheduleServiceTrait for ScheduleService {
+    fn schedules(&self, project: &ProjectInfo) -> super::Result<Vec<ScheduleInfo>> {
+        let now = Local::now();
+        let settings = self.project_service.settings(project)?;
+
+        let schedules = settings
+            .schedules
+            .into_iter()
+            .map(|schedule| ScheduleInfo::new(schedule, &now))
+            .collect();
+
+        Ok(schedules)
+    }
+
+    fn add(&self, project: &ProjectInfo, schedule: &NewSchedule) -> super::Result<ScheduleInfo> {
+        Schedule::parse_cron(&schedule.cron)?;
+
+        let mut added = None;
+        self.project_service
+            .modify_settings(project, &mut |settings| {
+                let id = settings
+                    .schedules
+                    .iter()
+                    .map(|schedule| schedule.id + 1)
+                    .max()
+                    .unwrap_or(1);
+
+                let new = Schedule {
+                    id,
+                    cron: schedule.cron.to_string(),
+                    action: schedule.action,
+                    enabled: schedule.enabled,
+                    last_run: None,
+                };
+
+                settings.schedules.push(new.clone());
+                added = Some(new);
+                Ok(())
+            })?;
+
+        let added = added.expect("the schedule was added");
+        Ok(ScheduleInfo::new(added, &Local::now()))
+    }
+
+    fn update(
+        &self,
+        project: &ProjectInfo,
+        id: u32,
+        schedule: &NewSchedule,
+    ) -> super::Result<ScheduleInfo> {
+        Schedule::parse_cron(&schedule.cron)?;
+
+        let mut updated = None;
+        self.project_service
+            .modify_settings(project, &mut |settings| {
+                if let Some(current) = settings
+                    .schedules
+                    .iter_mut()
+                    .find(|schedule| schedule.id == id)
+                {
+                    current.cron = schedule.cron.to_string();
+                    current.action = schedule.action;
+                    current.enabled = schedule.enabled;
+                    updated = Some(current.clone());
+                }
+                Ok(())
+            })?;
+
+        let updated = updated.ok_or(ScheduleServiceError::ScheduleNotFound {
+            project: project.name.to_string(),
+            id,
+        })?;
+
+        Ok(ScheduleInfo::new(updated, &Local::now()))
+    }
+
+    fn remove(&self, project: &ProjectInfo, id: u32) -> super::Result<()> {
+        let mut removed = false;
+        self.project_service
+            .modify_settings(project, &mut |settings| {
+                let count = settings.schedules.len();
+                settings.schedules.retain(|schedule| schedule.id != id);
+                removed = settings.schedules.len() < count;
+                Ok(())
+            })?;
+
+        if !removed {
+            return Err(ScheduleServiceError::ScheduleNotFound {
+                project: project.name.to_string(),
+                id,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn run_due(&self, since: &DateTime<Local>, now: &DateTime<Local>) {
+        let projects = match self.project_service.all_projects() {
+            Ok(projects) => projects,
+            Err(err) => {
+                error!("{}", err);
+                return;
+            }
+        };
+
+        for project in projects {
+            let settings = match self.project_service.settings(&project) {
+                Ok(settings) => settings,
+                Err(err) => {
+                    error!("{}", err);
+                    continue;
+                }
+            };
+
+            let due = settings
+                .schedules
+                .into_iter()
+                .filter(|schedule| {
+                    schedule
+                        .next_run(since)
+                        .is_some_and(|next_run| next_run <= *now)
+                })
+                .collect_vec();
+
+            for schedule in due {
+                self.run_schedule(&project, &schedule, now);
+            }
+        }
+    }
+}
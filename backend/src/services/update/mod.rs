@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::project::ProjectInfo;
+
+pub mod registry;
+pub mod service;
+
+pub type Result<T> = core::result::Result<T, Error>;
+pub type Error = UpdateServiceError;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum UpdateServiceError {
+    #[error("Invalid image reference {0}")]
+    InvalidImage(String),
+
+    #[error("Registry request for {image} failed - {error}")]
+    RegistryRequestFailed { image: String, error: String },
+
+    #[error("Registry did not return a digest for {0}")]
+    MissingDigest(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImageReference {
+    pub registry: String,
+    pub repository: String,
+    pub tag: String,
+    /// set if the image is pinned to a digest and can therefore never be outdated
+    pub digest: Option<String>,
+}
+
+impl ImageReference {
+    /// Parses references the way docker does - `nginx` is `registry-1.docker.io/library/nginx:latest`
+    pub fn parse(image: &str) -> Result<ImageReference> {
+        let invalid = || UpdateServiceError::InvalidImage(image.to_string());
+
+        let (name, digest) = match image.split_once('@') {
+            Some((name, digest)) => (name, Some(digest.to_string())),
+            None => (image, None),
+        };
+
+        let (name, tag) = match name.rsplit_once(':') {
+            Some((name, tag)) if !tag.contains('/') => (name, tag),
+            _ => (name, "latest"),
+        };
+
+        let (registry, repository) = match name.split_once('/') {
+            Some((registry, repository))
+                if registry.contains('.') || registry.contains(':') || registry == "localhost" =>
+            {
+                (registry, repository.to_string())
+            }
+            _ => ("docker.io", name.to_string()),
+        };
+
+        if repository.is_empty() || tag.is_empty() {
+            return Err(invalid());
+        }
+
+        let (registry, repository) = match registry {
+            "docker.io" if !repository.contains('/') => {
+                ("registry-1.docker.io", format!("library/{}", repository))
+            }
+            "docker.io" => ("registry-1.docker.io", repository),
+            _ => (registry, repository),
+        };
+
+        Ok(ImageReference {
+            registry: registry.to_string(),
+            repository,
+            tag: tag.to_string(),
+            digest,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ImageUpdate {
+    pub service: String,
+    pub image: String,
+    pub local_digest: Option<String>,
+    pub remote_digest: Option<String>,
+    pub update_available: bool,
+    pub error: Option<String>,
+}
+
+pub trait UpdateServiceTrait: Send + Sync {
+    /// Results of the last update check of the project
+    fn updates(&self, project: &ProjectInfo) -> Vec<ImageUpdate>;
+
+    fn update_available(&self, project: &ProjectInfo) -> bool {
+        self.updates(project)
+            .iter()
+            .any(|update| update.update_available)
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use reqwest::{
+    StatusCode,
+    header::{ACCEPT, WWW_AUTHENTICATE},
+};
+use serde::Deserialize;
+
+use super::{ImageReference, UpdateServiceError};
+
+const MANIFEST_TYPES: &str = "application/vnd.oci.image.index.v1+json, \
+    application/vnd.docker.distribution.manifest.list.v2+json, \
+    application/vnd.oci.image.manifest.v1+json, \
+    application/vnd.docker.distribution.manifest.v2+json";
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+/// Looks up the current digest of a tag through the registry HTTP API
+#[derive(Clone, Default)]
+pub struct RegistryClient {
+    client: reqwest::Client,
+    /// registries which are accessed via http instead of https
+    insecure: Vec<String>,
+}
+
+impl RegistryClient {
+    pub fn new(insecure: Vec<String>) -> RegistryClient {
+        Self {
+            client: reqwest::Client::new(),
+            insecure,
+        }
+    }
+
+    pub async fn remote_digest(&self, image: &ImageReference) -> super::Result<String> {
+        let to_error = |err: String| UpdateServiceError::RegistryRequestFailed {
+            image: format!("{}/{}:{}", image.registry, image.repository, image.tag),
+            error: err,
+        };
+
+        let scheme = if self.insecure.contains(&image.registry) {
+            "http"
+        } else {
+            "https"
+        };
+        let url = format!(
+            "{}://{}/v2/{}/manifests/{}",
+            scheme, image.registry, image.repository, image.tag
+        );
+
+        let mut response = self
+            .client
+            .head(&url)
+            .header(ACCEPT, MANIFEST_TYPES)
+            .send()
+            .await
+            .map_err(|err| to_error(err.to_string()))?;
+
+        if response.status() == StatusCode::UNAUTHORIZED {
+            let challenge = response
+                .headers()
+                .get(WWW_AUTHENTICATE)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| to_error("registry requires authentication".to_string()))?
+                .to_string();
+
+            let token = self
+                .anonymous_token(&challenge)
+                .await
+                .map_err(|err| to_error(err.to_string()))?;
+
+            response = self
+                .client
+                .head(&url)
+                .header(ACCEPT, MANIFEST_TYPES)
+                .bearer_auth(token)
+                .send()
+                .await
+                .map_err(|err| to_error(err.to_string()))?;
+        }
+
+        let response = response
+            .error_for_status()
+            .map_err(|err| to_error(err.to_string()))?;
+
+        response
+            .headers()
+            .get("Docker-Content-Digest")
+            .and_then(|value| value.to_str().ok())
+            .map(|digest| digest.to_string())
+            .ok_or_else(|| UpdateServiceError::MissingDigest(image.repository.to_string()))
+    }
+
+    /// Requests a pull token for the scope named in a `Bearer realm="..",service="..",scope=".."` challenge
+    async fn anonymous_token(&self, challenge: &str) -> reqwest::Result<String> {
+        let mut params = HashMap::new();
+        let mut rest = challenge.trim_start_matches("Bearer ");
+
+        // values are quoted and may contain commas themselves (`scope="repository:a:pull,push"`)
+        while let Some((key, value)) = rest.split_once("=\"") {
+            let Some((value, remaining)) = value.split_once('"') else {
+                break;
+            };
+
+            params.insert(key.trim_start_matches(',').trim(), value);
+            rest = remaining;
+        }
+
+        let realm = params.get("realm").copied().unwrap_or_default();
+        let query: Vec<(&str, &str)> = ["service", "scope"]
+            .into_iter()
+            .filter_map(|key| params.get(key).map(|value| (key, *value)))
+            .collect();
+
+        let response: TokenResponse = self
+            .client
+            .get(realm)
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(response.token.or(response.access_token).unwrap_or_default())
+    }
+}
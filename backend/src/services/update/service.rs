@@ -0,0 +1,186 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use tracing::{error, info};
+
+use crate::services::{
+    container::ContainerServiceTrait,
+    notification::{NotificationEvent, NotificationServiceTrait},
+    project::{ProjectInfo, ProjectServiceTrait},
+};
+
+use super::{ImageReference, ImageUpdate, UpdateServiceTrait, registry::RegistryClient};
+
+pub struct UpdateService {
+    project_service: Arc<dyn ProjectServiceTrait>,
+    container_service: Arc<dyn ContainerServiceTrait>,
+    notification_service: Arc<dyn NotificationServiceTrait>,
+    registry: RegistryClient,
+    checked: RwLock<HashMap<String, Vec<ImageUpdate>>>,
+}
+
+impl UpdateService {
+    pub fn new(
+        project_service: Arc<dyn ProjectServiceTrait>,
+        container_service: Arc<dyn ContainerServiceTrait>,
+        notification_service: Arc<dyn NotificationServiceTrait>,
+        registry: RegistryClient,
+    ) -> UpdateService {
+        Self {
+            project_service,
+            container_service,
+            notification_service,
+            registry,
+            checked: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Checks all projects every `interval` until the task is dropped
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+            self.check_all().await;
+        }
+    }
+
+    /// Checks all projects and applies the updates of projects with `auto_update` enabled
+    pub async fn check_all(&self) {
+        let projects = match self.project_service.all_projects() {
+            Ok(projects) => projects,
+            Err(err) => {
+                error!("{}", err);
+                return;
+            }
+        };
+
+        for project in projects {
+            let updates = self.check_project(&project).await;
+
+            let auto_update = self
+                .project_service
+                .settings(&project)
+                .is_ok_and(|settings| settings.auto_update);
+
+            if auto_update && updates.iter().any(|update| update.update_available) {
+                info!("applying image updates for {}", project.name);
+
+                let container_service = self.container_service.clone();
+                let notification_service = self.notification_service.clone();
+                let target = project.clone();
+                run_blocking(move || {
+                    let result = container_service
+                        .pull(&target)
+                        .and_then(|_| container_service.start(&target));
+                    notification_service.notify_outcome(
+                        &target,
+                        NotificationEvent::Started,
+                        &result,
+                    );
+                })
+                .await;
+
+                self.check_project(&project).await;
+            }
+        }
+    }
+
+    /// Compares the local digest of every service image against the registry and remembers the result
+    pub async fn check_project(&self, project: &ProjectInfo) -> Vec<ImageUpdate> {
+        // failures are already logged by the container service
+        let container_service = self.container_service.clone();
+        let target = project.clone();
+        let services = run_blocking(move || container_service.services(&target))
+            .await
+            .and_then(|services| services.ok())
+            .unwrap_or_default();
+
+        let mut updates = Vec::new();
+        let mut remote_digests = HashMap::new();
+
+        for service in services {
+            let mut update = ImageUpdate {
+                service: service.name,
+                image: service.image,
+                local_digest: None,
+                remote_digest: None,
+                update_available: false,
+                error: None,
+            };
+
+            let reference = match ImageReference::parse(&update.image) {
+                Ok(reference) => reference,
+                Err(err) => {
+                    update.error = Some(err.to_string());
+                    updates.push(update);
+                    continue;
+                }
+            };
+
+            let container_service = self.container_service.clone();
+            let image = update.image.to_string();
+            let local_digests = run_blocking(move || container_service.image_digests(&image))
+                .await
+                .and_then(|digests| digests.ok())
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|digest| digest.split_once('@').map(|(_, digest)| digest.to_string()))
+                .collect::<Vec<_>>();
+            update.local_digest = local_digests.first().cloned();
+
+            if let Some(digest) = &reference.digest {
+                update.remote_digest = Some(digest.to_string());
+                updates.push(update);
+                continue;
+            }
+
+            if !remote_digests.contains_key(&update.image) {
+                let digest = self.registry.remote_digest(&reference).await;
+                remote_digests.insert(update.image.to_string(), digest);
+            }
+
+            match &remote_digests[&update.image] {
+                Ok(digest) => {
+                    update.update_available =
+                        !local_digests.is_empty() && !local_digests.contains(digest);
+                    update.remote_digest = Some(digest.to_string());
+                }
+                Err(err) => update.error = Some(err.to_string()),
+            }
+
+            updates.push(update);
+        }
+
+        self.checked
+            .write()
+            .unwrap()
+            .insert(project.name.to_string(), updates.clone());
+
+        updates
+    }
+}
+
+/// Runs a docker command off the runtime, so a long pull doesn't stall the server
+async fn run_blocking<T: Send + 'static>(
+    command: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    tokio::task::spawn_blocking(command)
+        .await
+        .inspect_err(|err| error!("{}", err))
+        .ok()
+}
+
+impl UpdateServiceTrait for UpdateService {
+    fn updates(&self, project: &ProjectInfo) -> Vec<ImageUpdate> {
+        self.checked
+            .read()
+            .unwrap()
+            .get(&project.name)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
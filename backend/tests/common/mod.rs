@@ -1,2 +1,3 @@
 pub mod project_service;
 pub mod server;
+pub mod sinks;
@@ -6,7 +6,7 @@ use std::{
     path::PathBuf,
 };
 
-use backend::services::project::service::ProjectService;
+use backend::services::project::{secrets::SecretsKey, service::ProjectService};
 use tempfile::TempDir;
 
 pub fn test_project_service() -> (TempDir, ProjectService) {
@@ -38,10 +38,18 @@ pub fn test_project_service() -> (TempDir, ProjectService) {
         .write_all(b"sub file")
         .unwrap();
 
+    File::create(path_project_2.join("compose.yml"))
+        .unwrap()
+        .write_all(b"compose.yml")
+        .unwrap();
+
     File::create(path_project_3.join("compose.yml"))
         .unwrap()
         .write_all(b"compose.yml")
         .unwrap();
 
-    (dir, ProjectService::new(path))
+    (
+        dir,
+        ProjectService::new(path).with_secrets_key(SecretsKey::new("test key")),
+    )
 }
@@ -8,7 +8,20 @@ use std::{
 use axum_test::TestServer;
 use backend::{
     AdminAuth, Keys, app,
-    services::{container::ContainerServiceTrait, project::ProjectInfo},
+    services::{
+        container::{
+            ContainerServiceError, ContainerServiceTrait, DownOptions, ServiceInfo,
+            secrets::SecretsContainerService,
+        },
+        notification::{NotificationEvent, NotificationServiceTrait},
+        project::{
+            ProjectInfo,
+            roots::{DEFAULT_ROOT, ProjectRoot, RootPermissions},
+            secrets::SECRETS_DIR,
+        },
+        schedule::service::ScheduleService,
+        update::{registry::RegistryClient, service::UpdateService},
+    },
 };
 use cookie::Cookie;
 use serde_json::json;
@@ -16,8 +29,36 @@ use tempfile::TempDir;
 
 use crate::common::project_service::test_project_service;
 
+#[derive(Clone)]
 pub struct MockContainerService {
     data: Arc<Mutex<HashMap<String, bool>>>,
+    services: Arc<Mutex<HashMap<String, Vec<ServiceInfo>>>>,
+    digests: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    /// every `down` with its options, in call order
+    pub downs: Arc<Mutex<Vec<(String, DownOptions)>>>,
+    /// every `pull` with whether the secrets of the project were on disk during it
+    pub pulls: Arc<Mutex<Vec<(String, bool)>>>,
+    fail_down: Arc<Mutex<bool>>,
+}
+
+impl MockContainerService {
+    pub fn set_services(&self, project: &str, services: Vec<ServiceInfo>) {
+        self.services
+            .lock()
+            .unwrap()
+            .insert(project.to_string(), services);
+    }
+
+    pub fn fail_down(&self) {
+        *self.fail_down.lock().unwrap() = true;
+    }
+
+    pub fn set_image_digests(&self, image: &str, digests: Vec<String>) {
+        self.digests
+            .lock()
+            .unwrap()
+            .insert(image.to_string(), digests);
+    }
 }
 
 impl Default for MockContainerService {
@@ -30,6 +71,11 @@ impl Default for MockContainerService {
 
         MockContainerService {
             data: Arc::new(Mutex::new(map)),
+            services: Arc::new(Mutex::new(HashMap::new())),
+            digests: Arc::new(Mutex::new(HashMap::new())),
+            downs: Arc::new(Mutex::new(Vec::new())),
+            pulls: Arc::new(Mutex::new(Vec::new())),
+            fail_down: Arc::new(Mutex::new(false)),
         }
     }
 }
@@ -59,9 +105,32 @@ impl ContainerServiceTrait for MockContainerService {
             .unwrap_or(&false))
     }
 
+    fn services(
+        &self,
+        project: &ProjectInfo,
+    ) -> backend::services::container::Result<Vec<ServiceInfo>> {
+        Ok(self
+            .services
+            .lock()
+            .unwrap()
+            .get(&project.name)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn image_digests(&self, image: &str) -> backend::services::container::Result<Vec<String>> {
+        Ok(self
+            .digests
+            .lock()
+            .unwrap()
+            .get(image)
+            .cloned()
+            .unwrap_or_default())
+    }
+
     fn stop(&self, project: &ProjectInfo) -> backend::services::container::Result<()> {
         let mut data = self.data.lock().unwrap();
-        let state = data.get_mut(&project.name).unwrap();
+        let state = data.entry(project.name.to_string()).or_default();
 
         if *state {
             *state = false;
@@ -70,9 +139,28 @@ impl ContainerServiceTrait for MockContainerService {
         Ok(())
     }
 
+    fn down(
+        &self,
+        project: &ProjectInfo,
+        options: &DownOptions,
+    ) -> backend::services::container::Result<()> {
+        if *self.fail_down.lock().unwrap() {
+            return Err(ContainerServiceError::FailedToExecCommand {
+                error: "docker is not available".to_string(),
+                command: "compose down".to_string(),
+            });
+        }
+
+        self.downs
+            .lock()
+            .unwrap()
+            .push((project.name.to_string(), *options));
+        self.stop(project)
+    }
+
     fn start(&self, project: &ProjectInfo) -> backend::services::container::Result<()> {
         let mut data = self.data.lock().unwrap();
-        let state = data.get_mut(&project.name).unwrap();
+        let state = data.entry(project.name.to_string()).or_default();
 
         if !*state {
             *state = true;
@@ -81,35 +169,125 @@ impl ContainerServiceTrait for MockContainerService {
         Ok(())
     }
 
-    fn pull(&self, _project: &ProjectInfo) -> backend::services::container::Result<()> {
+    fn pull(&self, project: &ProjectInfo) -> backend::services::container::Result<()> {
+        self.pulls.lock().unwrap().push((
+            project.name.to_string(),
+            project.dir.join(SECRETS_DIR).exists(),
+        ));
         Ok(())
     }
 }
 
+#[derive(Clone, Default)]
+pub struct MockNotificationService {
+    pub sent: Arc<Mutex<Vec<(String, NotificationEvent)>>>,
+}
+
+impl NotificationServiceTrait for MockNotificationService {
+    fn notify(&self, project: &ProjectInfo, event: NotificationEvent, _message: &str) {
+        self.sent
+            .lock()
+            .unwrap()
+            .push((project.name.to_string(), event));
+    }
+}
+
 pub fn test_server() -> (TempDir, TestServer) {
+    let (dir, server, _) = notification_test_server();
+    (dir, server)
+}
+
+pub fn notification_test_server() -> (TempDir, TestServer, MockNotificationService) {
+    let (dir, server, notification_service, _) = build_test_server(true, RootPermissions::ALL);
+    (dir, server, notification_service)
+}
+
+/// Logged in users of this server may not reveal secrets
+pub async fn masked_test_server() -> (TempDir, TestServer) {
+    let (dir, mut server, _, _) = build_test_server(false, RootPermissions::ALL);
+    login(&mut server).await;
+
+    (dir, server)
+}
+
+/// Logged in server together with the container service behind it
+pub async fn container_test_server() -> (TempDir, TestServer, MockContainerService) {
+    let (dir, mut server, _, container_service) = build_test_server(true, RootPermissions::ALL);
+    login(&mut server).await;
+
+    (dir, server, container_service)
+}
+
+/// Logged in server whose projects lie in a read-only root
+pub async fn read_only_test_server() -> (TempDir, TestServer, MockContainerService) {
+    let (dir, mut server, _, container_service) =
+        build_test_server(true, RootPermissions::READ_ONLY);
+    login(&mut server).await;
+
+    (dir, server, container_service)
+}
+
+fn build_test_server(
+    reveal_secrets: bool,
+    permissions: RootPermissions,
+) -> (
+    TempDir,
+    TestServer,
+    MockNotificationService,
+    MockContainerService,
+) {
     let (dir, project_service) = test_project_service();
+    let project_service = project_service
+        .with_root(ProjectRoot::new(DEFAULT_ROOT, dir.path().into()).with_permissions(permissions));
 
     let project_service = Arc::new(project_service);
-    let container_service = Arc::new(MockContainerService::default());
+    let mock_container_service = MockContainerService::default();
+    let container_service = Arc::new(SecretsContainerService::new(
+        Arc::new(mock_container_service.clone()),
+        project_service.clone(),
+    ));
+    let notification_service = MockNotificationService::default();
+    let schedule_service = Arc::new(ScheduleService::new(
+        project_service.clone(),
+        container_service.clone(),
+        Arc::new(notification_service.clone()),
+    ));
+    let update_service = Arc::new(UpdateService::new(
+        project_service.clone(),
+        container_service.clone(),
+        Arc::new(notification_service.clone()),
+        RegistryClient::default(),
+    ));
     let app = app(
         project_service.clone(),
         container_service.clone(),
+        Arc::new(notification_service.clone()),
+        schedule_service,
+        update_service,
         Keys::new("secret".as_bytes()),
         AdminAuth {
             name: "admin".to_string(),
             password: "password".to_string(),
+            reveal_secrets,
         },
     );
 
     (
         dir,
         TestServer::builder().http_transport().build(app).unwrap(),
+        notification_service,
+        mock_container_service,
     )
 }
 
 pub async fn auth_test_server() -> (TempDir, TestServer, String) {
     let (dir, mut server) = test_server();
+    let token = login(&mut server).await;
 
+    (dir, server, token)
+}
+
+pub async fn login(server: &mut TestServer) -> String {
     let response = server
         .post("/auth")
         .json(&json!({
@@ -123,5 +301,5 @@ pub async fn auth_test_server() -> (TempDir, TestServer, String) {
 
     server.add_cookie(Cookie::new("token", token.clone()));
 
-    (dir, server, token)
+    token
 }
@@ -0,0 +1,169 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, Uri},
+    response::IntoResponse,
+    routing::{any, get},
+};
+use serde_json::json;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    pub path: String,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+/// Local HTTP server recording every request it receives
+pub async fn http_sink() -> (SocketAddr, Arc<Mutex<Vec<ReceivedRequest>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    async fn record(
+        State(received): State<Arc<Mutex<Vec<ReceivedRequest>>>>,
+        uri: Uri,
+        headers: HeaderMap,
+        body: Bytes,
+    ) {
+        received.lock().unwrap().push(ReceivedRequest {
+            path: uri.to_string(),
+            headers,
+            body: String::from_utf8_lossy(&body).to_string(),
+        });
+    }
+
+    let app = Router::new()
+        .fallback(any(record))
+        .with_state(received.clone());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (addr, received)
+}
+
+/// Minimal plain text SMTP server recording the data of every mail it receives
+pub async fn smtp_sink() -> (SocketAddr, Arc<Mutex<Vec<String>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mails = received.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mails = mails.clone();
+
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+
+                write.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let command = line.to_uppercase();
+
+                    if command.starts_with("DATA") {
+                        write.write_all(b"354 go ahead\r\n").await.unwrap();
+
+                        let mut data = Vec::new();
+                        while let Ok(Some(line)) = lines.next_line().await {
+                            if line == "." {
+                                break;
+                            }
+                            data.push(line);
+                        }
+
+                        mails.lock().unwrap().push(data.join("\n"));
+                        write.write_all(b"250 queued\r\n").await.unwrap();
+                    } else if command.starts_with("QUIT") {
+                        write.write_all(b"221 bye\r\n").await.unwrap();
+                        break;
+                    } else {
+                        write.write_all(b"250 OK\r\n").await.unwrap();
+                    }
+                }
+            });
+        }
+    });
+
+    (addr, received)
+}
+
+#[derive(Clone)]
+struct RegistryState {
+    addr: SocketAddr,
+    /// `repository:tag` -> digest
+    digests: HashMap<String, String>,
+    require_token: bool,
+}
+
+/// Docker registry stub answering manifest requests with the given digests.
+/// If `require_token` is set, clients have to fetch an anonymous token first
+pub async fn registry_stub(digests: HashMap<String, String>, require_token: bool) -> SocketAddr {
+    async fn manifest(
+        State(state): State<RegistryState>,
+        Path(path): Path<String>,
+        headers: HeaderMap,
+    ) -> impl IntoResponse {
+        let Some((repository, tag)) = path.rsplit_once("/manifests/") else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+
+        let authorized = headers
+            .get("authorization")
+            .is_some_and(|value| value == "Bearer stub-token");
+
+        if state.require_token && !authorized {
+            let challenge = format!(
+                "Bearer realm=\"http://{}/token\",service=\"stub\",scope=\"repository:{}:pull\"",
+                state.addr, repository
+            );
+            return (StatusCode::UNAUTHORIZED, [("WWW-Authenticate", challenge)]).into_response();
+        }
+
+        match state.digests.get(&format!("{}:{}", repository, tag)) {
+            Some(digest) => {
+                (StatusCode::OK, [("Docker-Content-Digest", digest.clone())]).into_response()
+            }
+            None => StatusCode::NOT_FOUND.into_response(),
+        }
+    }
+
+    async fn token() -> impl IntoResponse {
+        Json(json!({ "token": "stub-token" }))
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let state = RegistryState {
+        addr,
+        digests,
+        require_token,
+    };
+
+    let app = Router::new()
+        .route("/v2/{*path}", any(manifest))
+        .route("/token", get(token))
+        .with_state(state);
+
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    addr
+}
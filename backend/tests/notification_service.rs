@@ -0,0 +1,284 @@
+use std::{sync::Arc, time::Duration};
+
+use backend::services::{
+    container::ServiceInfo,
+    notification::{
+        Notification, NotificationChannel, NotificationEvent, NotificationServiceError,
+        NotificationServiceTrait, NotificationSettings,
+        service::{NotificationService, SmtpConfig, SmtpTls},
+        watcher::CrashWatcher,
+    },
+    project::{ProjectServiceTrait, ProjectSettings},
+};
+use common::{
+    project_service::test_project_service,
+    server::{MockContainerService, MockNotificationService},
+    sinks::{http_sink, smtp_sink},
+};
+use serde_json::json;
+
+mod common;
+
+fn notification() -> Notification {
+    Notification {
+        project: "project1".to_string(),
+        event: NotificationEvent::Crashed,
+        message: "Service db of Project project1 crashed".to_string(),
+        timestamp: 42,
+    }
+}
+
+fn notification_service(smtp: Option<SmtpConfig>) -> NotificationService {
+    let (_dir, project_service) = test_project_service();
+    NotificationService::new(Arc::new(project_service), smtp)
+}
+
+#[tokio::test]
+async fn deliver_webhook() {
+    let (addr, received) = http_sink().await;
+    let service = notification_service(None);
+
+    service
+        .deliver(
+            &NotificationChannel::Webhook {
+                url: format!("http://{}/hook", addr),
+            },
+            &notification(),
+        )
+        .await
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].path, "/hook");
+
+    let body: serde_json::Value = serde_json::from_str(&received[0].body).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "project": "project1",
+            "event": "crashed",
+            "message": "Service db of Project project1 crashed",
+            "timestamp": 42
+        })
+    );
+}
+
+#[tokio::test]
+async fn deliver_ntfy() {
+    let (addr, received) = http_sink().await;
+    let service = notification_service(None);
+
+    service
+        .deliver(
+            &NotificationChannel::Ntfy {
+                url: format!("http://{}/yard", addr),
+                token: Some("secret".to_string()),
+            },
+            &notification(),
+        )
+        .await
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received[0].path, "/yard");
+    assert_eq!(received[0].headers["title"], "project1 crashed");
+    assert_eq!(received[0].headers["authorization"], "Bearer secret");
+    assert_eq!(received[0].body, "Service db of Project project1 crashed");
+}
+
+#[tokio::test]
+async fn deliver_gotify() {
+    let (addr, received) = http_sink().await;
+    let service = notification_service(None);
+
+    service
+        .deliver(
+            &NotificationChannel::Gotify {
+                url: format!("http://{}/", addr),
+                token: "app-token".to_string(),
+            },
+            &notification(),
+        )
+        .await
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received[0].path, "/message");
+    assert_eq!(received[0].headers["x-gotify-key"], "app-token");
+
+    let body: serde_json::Value = serde_json::from_str(&received[0].body).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "title": "project1 crashed",
+            "message": "Service db of Project project1 crashed"
+        })
+    );
+}
+
+#[tokio::test]
+async fn deliver_webhook_unreachable() {
+    let service = notification_service(None);
+
+    let result = service
+        .deliver(
+            &NotificationChannel::Webhook {
+                url: "http://127.0.0.1:1/hook".to_string(),
+            },
+            &notification(),
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(NotificationServiceError::DeliveryFailed { channel, .. }) if channel == "webhook"
+    ));
+}
+
+#[tokio::test]
+async fn deliver_email() {
+    let (addr, received) = smtp_sink().await;
+    let service = notification_service(Some(SmtpConfig {
+        host: addr.ip().to_string(),
+        port: addr.port(),
+        tls: SmtpTls::None,
+        credentials: None,
+        from: "yard@example.com".to_string(),
+    }));
+
+    service
+        .deliver(
+            &NotificationChannel::Email {
+                to: vec!["ops@example.com".to_string()],
+            },
+            &notification(),
+        )
+        .await
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+    assert!(received[0].contains("Subject: [ContainerYard] project1 crashed"));
+    assert!(received[0].contains("To: ops@example.com"));
+    assert!(received[0].contains("Service db of Project project1 crashed"));
+}
+
+#[tokio::test]
+async fn deliver_email_not_configured() {
+    let service = notification_service(None);
+
+    let result = service
+        .deliver(
+            &NotificationChannel::Email {
+                to: vec!["ops@example.com".to_string()],
+            },
+            &notification(),
+        )
+        .await;
+
+    assert_eq!(result, Err(NotificationServiceError::EmailNotConfigured));
+}
+
+#[tokio::test]
+async fn notify_filters_events() {
+    let (addr, received) = http_sink().await;
+    let (_dir, project_service) = test_project_service();
+    let project_service = Arc::new(project_service);
+
+    let project_info = project_service.project("project1").unwrap();
+    project_service
+        .update_settings(
+            &project_info,
+            &ProjectSettings {
+                notifications: NotificationSettings {
+                    channels: vec![NotificationChannel::Webhook {
+                        url: format!("http://{}/hook", addr),
+                    }],
+                    events: vec![NotificationEvent::Crashed],
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let service = NotificationService::new(project_service, None);
+    service.notify(&project_info, NotificationEvent::Started, "started");
+    service.notify(&project_info, NotificationEvent::Crashed, "crashed");
+
+    for _ in 0..50 {
+        if !received.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+    assert!(received[0].body.contains("\"event\":\"crashed\""));
+}
+
+#[tokio::test]
+async fn crash_watcher_reports_once() {
+    let (_dir, project_service) = test_project_service();
+    let container_service = MockContainerService::default();
+    let notification_service = MockNotificationService::default();
+
+    let mut watcher = CrashWatcher::new(
+        Arc::new(project_service),
+        Arc::new(container_service.clone()),
+        Arc::new(notification_service.clone()),
+    );
+
+    let service = |state: &str, exit_code| ServiceInfo {
+        name: "db".to_string(),
+        image: "postgres:17".to_string(),
+        state: state.to_string(),
+        exit_code,
+    };
+
+    container_service.set_services("project1", vec![service("running", 0)]);
+    watcher.check();
+    assert!(notification_service.sent.lock().unwrap().is_empty());
+
+    container_service.set_services("project1", vec![service("exited", 1)]);
+    watcher.check();
+    watcher.check();
+    assert_eq!(
+        *notification_service.sent.lock().unwrap(),
+        vec![("project1".to_string(), NotificationEvent::Crashed)]
+    );
+
+    container_service.set_services("project1", vec![service("running", 0)]);
+    watcher.check();
+    container_service.set_services("project1", vec![service("restarting", 0)]);
+    watcher.check();
+    assert_eq!(notification_service.sent.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn crash_watcher_ignores_clean_exit() {
+    let (_dir, project_service) = test_project_service();
+    let container_service = MockContainerService::default();
+    let notification_service = MockNotificationService::default();
+
+    let mut watcher = CrashWatcher::new(
+        Arc::new(project_service),
+        Arc::new(container_service.clone()),
+        Arc::new(notification_service.clone()),
+    );
+
+    container_service.set_services(
+        "project1",
+        vec![ServiceInfo {
+            name: "migrate".to_string(),
+            image: "app:latest".to_string(),
+            state: "exited".to_string(),
+            exit_code: 0,
+        }],
+    );
+    watcher.check();
+
+    assert!(notification_service.sent.lock().unwrap().is_empty());
+}
@@ -0,0 +1,110 @@
+use std::{fs, os::unix::fs::symlink};
+
+use backend::services::project::{
+    ProjectServiceTrait,
+    discovery::{IGNORE_FILE, IgnoreRules, SkipReason, SkippedDir},
+    roots::DEFAULT_ROOT,
+};
+use common::project_service::test_project_service;
+use tempfile::TempDir;
+
+mod common;
+
+fn names(project_service: &dyn ProjectServiceTrait) -> Vec<String> {
+    project_service
+        .all_projects()
+        .unwrap()
+        .into_iter()
+        .map(|project| project.name)
+        .collect()
+}
+
+#[tokio::test]
+async fn requires_compose_file() {
+    let (dir, project_service) = test_project_service();
+    fs::create_dir(dir.path().join(".git")).unwrap();
+    fs::create_dir(dir.path().join("legacy")).unwrap();
+    fs::write(dir.path().join("legacy").join("docker-compose.yaml"), "").unwrap();
+    // a dir named like a compose file doesn't count
+    fs::create_dir_all(dir.path().join("broken").join("compose.yml")).unwrap();
+
+    assert_eq!(
+        names(&project_service),
+        vec!["legacy", "project1", "project2", "project3"]
+    );
+
+    let skipped = project_service.discover().unwrap().skipped;
+    assert_eq!(
+        skipped,
+        vec![
+            SkippedDir {
+                root: DEFAULT_ROOT.to_string(),
+                dir: dir.path().join(".git"),
+                reason: SkipReason::NoComposeFile,
+            },
+            SkippedDir {
+                root: DEFAULT_ROOT.to_string(),
+                dir: dir.path().join("broken"),
+                reason: SkipReason::NoComposeFile,
+            },
+        ]
+    );
+
+    // skipped dirs can still be opened to add a compose file
+    assert!(project_service.project("broken").is_ok());
+}
+
+#[tokio::test]
+async fn ignore_file() {
+    let (dir, project_service) = test_project_service();
+    fs::write(
+        dir.path().join(IGNORE_FILE),
+        "# old stacks\nproject?\n!project3\n",
+    )
+    .unwrap();
+
+    assert_eq!(names(&project_service), vec!["project3"]);
+
+    let skipped = project_service.discover().unwrap().skipped;
+    assert_eq!(
+        skipped
+            .iter()
+            .map(|skipped| &skipped.reason)
+            .collect::<Vec<_>>(),
+        vec![
+            &SkipReason::Ignored {
+                pattern: "project?".to_string()
+            },
+            &SkipReason::Ignored {
+                pattern: "project?".to_string()
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn symlink_outside_root_is_reported() {
+    let (dir, project_service) = test_project_service();
+    let outside = TempDir::new().unwrap();
+    symlink(outside.path(), dir.path().join("escape")).unwrap();
+
+    assert_eq!(
+        project_service.discover().unwrap().skipped,
+        vec![SkippedDir {
+            root: DEFAULT_ROOT.to_string(),
+            dir: dir.path().join("escape"),
+            reason: SkipReason::OutsideRoot,
+        }]
+    );
+}
+
+#[test]
+fn ignore_rules() {
+    let rules = IgnoreRules::parse("  # comment\n\n*.bak\nbackup-*/\n!backup-keep\n");
+
+    assert_eq!(rules.ignoring("app.bak"), Some("*.bak"));
+    assert_eq!(rules.ignoring("backup-2024"), Some("backup-*"));
+    assert_eq!(rules.ignoring("backup-keep"), None);
+    assert_eq!(rules.ignoring("app"), None);
+    assert_eq!(rules.ignoring("# comment"), None);
+}
@@ -0,0 +1,106 @@
+use backend::services::{
+    container::{DownOptions, RemoveImages},
+    project::{
+        ProjectServiceTrait,
+        removal::{DeleteStep, DeleteStepKind, DeleteStepStatus, delete_project},
+    },
+};
+use common::{project_service::test_project_service, server::MockContainerService};
+
+mod common;
+
+#[tokio::test]
+async fn delete_project_with_down() {
+    let (dir, project_service) = test_project_service();
+    let container_service = MockContainerService::default();
+
+    let project_info = project_service.project("project1").unwrap();
+    let options = DownOptions {
+        volumes: true,
+        rmi: Some(RemoveImages::Local),
+    };
+
+    let steps = delete_project(
+        &project_service,
+        &container_service,
+        &project_info,
+        Some(&options),
+    );
+
+    assert_eq!(
+        steps,
+        vec![
+            DeleteStep {
+                step: DeleteStepKind::Down,
+                status: DeleteStepStatus::Ok,
+                error: None
+            },
+            DeleteStep {
+                step: DeleteStepKind::Trash,
+                status: DeleteStepStatus::Ok,
+                error: None
+            }
+        ]
+    );
+    assert_eq!(
+        *container_service.downs.lock().unwrap(),
+        vec![("project1".to_string(), options)]
+    );
+    assert!(!dir.path().join("project1").exists());
+}
+
+#[tokio::test]
+async fn delete_project_without_down() {
+    let (_dir, project_service) = test_project_service();
+    let container_service = MockContainerService::default();
+
+    let project_info = project_service.project("project2").unwrap();
+
+    let steps = delete_project(&project_service, &container_service, &project_info, None);
+
+    assert_eq!(
+        steps,
+        vec![DeleteStep {
+            step: DeleteStepKind::Trash,
+            status: DeleteStepStatus::Ok,
+            error: None
+        }]
+    );
+    assert!(container_service.downs.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn failed_down_keeps_project() {
+    let (dir, project_service) = test_project_service();
+    let container_service = MockContainerService::default();
+    container_service.fail_down();
+
+    let project_info = project_service.project("project1").unwrap();
+
+    let steps = delete_project(
+        &project_service,
+        &container_service,
+        &project_info,
+        Some(&DownOptions::default()),
+    );
+
+    assert_eq!(
+        steps,
+        vec![
+            DeleteStep {
+                step: DeleteStepKind::Down,
+                status: DeleteStepStatus::Failed,
+                error: Some(
+                    "Failed to exec command 'compose down' - docker is not available".to_string()
+                )
+            },
+            DeleteStep {
+                step: DeleteStepKind::Trash,
+                status: DeleteStepStatus::Skipped,
+                error: None
+            }
+        ]
+    );
+    assert!(dir.path().join("project1").exists());
+    assert_eq!(project_service.trash(), Ok(vec![]));
+}
@@ -0,0 +1,333 @@
+use std::{fs, os::unix::fs::MetadataExt, path::PathBuf};
+
+use backend::services::project::{
+    DesiredState, ProjectInfo, ProjectServiceError, ProjectServiceTrait,
+    metadata::ProjectMetadata,
+    roots::{DEFAULT_ROOT, ProjectRoot, RootAction, RootPermissions, parse_roots},
+    service::ProjectService,
+};
+use common::project_service::test_project_service;
+use tempfile::TempDir;
+
+mod common;
+
+/// The projects of the test service plus a root `infra` holding `traefik`
+fn multi_root_service(permissions: RootPermissions) -> (TempDir, TempDir, ProjectService) {
+    let (dir, project_service) = test_project_service();
+
+    let infra = TempDir::new().unwrap();
+    fs::create_dir(infra.path().join("traefik")).unwrap();
+    fs::write(infra.path().join("traefik").join("compose.yml"), "traefik").unwrap();
+
+    let project_service = project_service
+        .with_root(ProjectRoot::new("infra", infra.path().into()).with_permissions(permissions));
+
+    (dir, infra, project_service)
+}
+
+#[tokio::test]
+async fn all_projects_merges_roots() {
+    let (dir, infra, project_service) = multi_root_service(RootPermissions::ALL);
+
+    let names: Vec<String> = project_service
+        .all_projects()
+        .unwrap()
+        .into_iter()
+        .map(|project| project.name)
+        .collect();
+
+    assert_eq!(
+        names,
+        vec!["infra:traefik", "project1", "project2", "project3"]
+    );
+
+    assert_eq!(
+        project_service.project("infra:traefik"),
+        Ok(ProjectInfo {
+            name: "infra:traefik".to_string(),
+            dir: infra.path().join("traefik"),
+        })
+    );
+    assert_eq!(
+        project_service.project("project1"),
+        Ok(ProjectInfo {
+            name: "project1".to_string(),
+            dir: dir.path().join("project1"),
+        })
+    );
+    // only known roots are prefixes, the default root is never one
+    assert_eq!(
+        project_service.project("default:project1"),
+        Err(ProjectServiceError::ProjectNotFound(
+            "default:project1".to_string()
+        ))
+    );
+    assert_eq!(
+        project_service.project("infra:project1"),
+        Err(ProjectServiceError::ProjectNotFound(
+            "infra:project1".to_string()
+        ))
+    );
+}
+
+#[tokio::test]
+async fn missing_root_is_skipped() {
+    let (_dir, project_service) = test_project_service();
+    let project_service =
+        project_service.with_root(ProjectRoot::new("gone", PathBuf::from("/does/not/exist")));
+
+    assert_eq!(project_service.all_projects().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn create_in_root() {
+    let (_dir, infra, project_service) = multi_root_service(RootPermissions::ALL);
+
+    let project_info = project_service.create("infra:dns").unwrap();
+
+    assert_eq!(project_info.name, "infra:dns");
+    assert_eq!(project_info.dir, infra.path().join("dns"));
+    assert_eq!(project_info.root(), Some("infra"));
+    assert_eq!(project_info.compose_name(), "infra_dns");
+    assert!(infra.path().join("dns").join("compose.yml").exists());
+}
+
+#[tokio::test]
+async fn compose_name_per_root() {
+    let (_dir, infra, project_service) = multi_root_service(RootPermissions::ALL);
+    fs::create_dir(infra.path().join("project1")).unwrap();
+    fs::write(infra.path().join("project1").join("compose.yml"), "web").unwrap();
+
+    let default = project_service.project("project1").unwrap();
+    let other = project_service.project("infra:project1").unwrap();
+
+    assert_eq!(default.root(), None);
+    assert_eq!(default.compose_name(), "project1");
+    assert_eq!(other.compose_name(), "infra_project1");
+
+    let project_info = ProjectInfo {
+        name: "Tools:My.App".to_string(),
+        dir: PathBuf::from("/srv/tools/My.App"),
+    };
+    assert_eq!(project_info.compose_name(), "tools_myapp");
+}
+
+#[tokio::test]
+async fn read_only_root() {
+    let (_dir, infra, project_service) = multi_root_service(RootPermissions::READ_ONLY);
+    let traefik = project_service.project("infra:traefik").unwrap();
+
+    let denied = |action| ProjectServiceError::RootPermissionDenied {
+        root: "infra".to_string(),
+        action,
+    };
+
+    assert_eq!(
+        project_service.read_file(&traefik, "compose.yml"),
+        Ok("traefik".to_string())
+    );
+    assert_eq!(
+        project_service.update_file(&traefik, "compose.yml", "changed"),
+        Err(denied(RootAction::Edit))
+    );
+    assert_eq!(
+        project_service.create_dir(&traefik, "config"),
+        Err(denied(RootAction::Edit))
+    );
+    assert_eq!(
+        project_service.delete_file(&traefik, "compose.yml"),
+        Err(denied(RootAction::Delete))
+    );
+    assert_eq!(
+        project_service.delete(&traefik),
+        Err(denied(RootAction::Delete))
+    );
+    assert_eq!(
+        project_service.create("infra:dns"),
+        Err(denied(RootAction::Create))
+    );
+
+    let project1 = project_service.project("project1").unwrap();
+    assert_eq!(
+        project_service.duplicate(&project1, "infra:copy"),
+        Err(denied(RootAction::Create))
+    );
+    assert_eq!(
+        project_service.rename(&traefik, "traefik"),
+        Err(denied(RootAction::Delete))
+    );
+
+    assert_eq!(
+        fs::read_to_string(infra.path().join("traefik").join("compose.yml")).unwrap(),
+        "traefik"
+    );
+
+    // settings are configuration just like files
+    let mut settings = project_service.settings(&traefik).unwrap();
+    settings.dependencies = vec!["project1".to_string()];
+    assert_eq!(
+        project_service.update_settings(&traefik, &settings),
+        Err(denied(RootAction::Edit))
+    );
+    assert_eq!(
+        project_service
+            .set_secret(&traefik, "TOKEN", "abc")
+            .map(|_| ()),
+        Err(denied(RootAction::Edit))
+    );
+    assert_eq!(
+        project_service
+            .update_metadata(
+                &traefik,
+                ProjectMetadata {
+                    tags: vec!["infra".to_string()],
+                    ..Default::default()
+                }
+            )
+            .map(|_| ()),
+        Err(denied(RootAction::Edit))
+    );
+
+    // what is recorded about running the project is kept up to date
+    assert_eq!(
+        project_service.set_desired_state(&traefik, DesiredState::Running),
+        Ok(())
+    );
+    assert_eq!(
+        project_service.settings(&traefik).unwrap().desired_state,
+        Some(DesiredState::Running)
+    );
+}
+
+#[tokio::test]
+async fn partial_permissions() {
+    let permissions = RootPermissions {
+        create: false,
+        edit: true,
+        delete: false,
+    };
+    let (_dir, _infra, project_service) = multi_root_service(permissions);
+    let traefik = project_service.project("infra:traefik").unwrap();
+
+    assert_eq!(
+        project_service.update_file(&traefik, "compose.yml", "changed"),
+        Ok("changed".to_string())
+    );
+    assert_eq!(
+        project_service.delete(&traefik),
+        Err(ProjectServiceError::RootPermissionDenied {
+            root: "infra".to_string(),
+            action: RootAction::Delete,
+        })
+    );
+}
+
+#[tokio::test]
+async fn trash_per_root() {
+    let (dir, infra, project_service) = multi_root_service(RootPermissions::ALL);
+    let traefik = project_service.project("infra:traefik").unwrap();
+    let project2 = project_service.project("project2").unwrap();
+
+    project_service.delete(&project2).unwrap();
+    project_service.delete(&traefik).unwrap();
+
+    let trash = project_service.trash().unwrap();
+    assert_eq!(trash.len(), 2);
+    assert_eq!(trash[0].project, "infra:traefik");
+    assert_eq!(trash[1].project, "project2");
+    assert!(!infra.path().join("traefik").exists());
+
+    project_service.restore(&trash[0].id).unwrap();
+    assert!(infra.path().join("traefik").join("compose.yml").exists());
+    assert!(!dir.path().join("traefik").exists());
+
+    project_service.purge(&trash[1].id).unwrap();
+    assert_eq!(project_service.trash(), Ok(Vec::new()));
+}
+
+#[tokio::test]
+async fn rename_into_other_root() {
+    let (dir, infra, project_service) = multi_root_service(RootPermissions::ALL);
+    let project3 = project_service.project("project3").unwrap();
+
+    let renamed = project_service.rename(&project3, "infra:project3").unwrap();
+
+    assert_eq!(renamed.name, "infra:project3");
+    assert!(infra.path().join("project3").join("compose.yml").exists());
+    assert!(!dir.path().join("project3").exists());
+}
+
+#[tokio::test]
+async fn rename_into_root_on_other_file_system() {
+    let (dir, project_service) = test_project_service();
+    // tmpfs is usually mounted there, the test only means something on another device
+    let Ok(other) = TempDir::new_in("/dev/shm") else {
+        return;
+    };
+    if fs::metadata(other.path()).unwrap().dev() == fs::metadata(dir.path()).unwrap().dev() {
+        return;
+    }
+
+    let project_service = project_service.with_root(ProjectRoot::new("other", other.path().into()));
+    let project1 = project_service.project("project1").unwrap();
+    project_service
+        .update_metadata(
+            &project1,
+            ProjectMetadata {
+                tags: vec!["web".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let renamed = project_service.rename(&project1, "other:project1").unwrap();
+
+    assert_eq!(renamed.name, "other:project1");
+    for file in ["compose.yml", ".env", "sub/text.txt"] {
+        assert!(other.path().join("project1").join(file).exists());
+    }
+    assert_eq!(
+        project_service.settings(&renamed).unwrap().metadata.tags,
+        vec!["web".to_string()]
+    );
+    assert!(!dir.path().join("project1").exists());
+}
+
+#[tokio::test]
+async fn replace_default_root() {
+    let (dir, project_service) = test_project_service();
+    let project_service = project_service.with_root(
+        ProjectRoot::new(DEFAULT_ROOT, dir.path().into())
+            .with_permissions(RootPermissions::READ_ONLY),
+    );
+
+    assert_eq!(project_service.roots().len(), 1);
+    assert_eq!(
+        project_service.create("new").map(|_| ()),
+        Err(ProjectServiceError::RootPermissionDenied {
+            root: DEFAULT_ROOT.to_string(),
+            action: RootAction::Create,
+        })
+    );
+}
+
+#[test]
+fn parse_root_list() {
+    assert_eq!(
+        parse_roots("apps=/srv/apps, infra=/opt/infra:ro,backup=/mnt/backup:create+edit"),
+        Ok(vec![
+            ProjectRoot::new("apps", "/srv/apps".into()),
+            ProjectRoot::new("infra", "/opt/infra".into())
+                .with_permissions(RootPermissions::READ_ONLY),
+            ProjectRoot::new("backup", "/mnt/backup".into()).with_permissions(RootPermissions {
+                create: true,
+                edit: true,
+                delete: false,
+            }),
+        ])
+    );
+
+    assert!(parse_roots("/srv/apps").is_err());
+    assert!(parse_roots("in:fra=/opt/infra").is_err());
+    assert!(parse_roots("infra=/opt/infra:write").is_err());
+}
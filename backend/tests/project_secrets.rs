@@ -0,0 +1,259 @@
+use std::{fs, os::unix::fs::PermissionsExt, sync::Arc};
+
+use backend::services::{
+    container::{
+        ContainerServiceError, ContainerServiceTrait, DownOptions, secrets::SecretsContainerService,
+    },
+    project::{
+        ProjectServiceError, ProjectServiceTrait, SETTINGS_FILE,
+        secrets::{SALT_LEN, SECRETS_DIR, SECRETS_ENV_FILE, SecretsKey},
+        service::ProjectService,
+    },
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use common::{project_service::test_project_service, server::MockContainerService};
+
+mod common;
+
+#[tokio::test]
+async fn secrets_are_encrypted_at_rest() {
+    let (dir, project_service) = test_project_service();
+    let project_info = project_service.project("project1").unwrap();
+
+    let secret = project_service
+        .set_secret(&project_info, "DB_PASSWORD", "hunter2")
+        .unwrap();
+
+    assert_eq!(secret.name, "DB_PASSWORD");
+    assert_eq!(
+        project_service.secrets(&project_info).unwrap(),
+        vec![secret]
+    );
+
+    let settings = fs::read_to_string(dir.path().join("project1").join(SETTINGS_FILE)).unwrap();
+    assert!(settings.contains("DB_PASSWORD"));
+    assert!(!settings.contains("hunter2"));
+
+    // the same value never encrypts to the same ciphertext
+    project_service
+        .set_secret(&project_info, "OTHER", "hunter2")
+        .unwrap();
+    let settings = project_service.settings(&project_info).unwrap();
+    assert_ne!(
+        settings.secrets["DB_PASSWORD"].value,
+        settings.secrets["OTHER"].value
+    );
+}
+
+#[tokio::test]
+async fn secrets_disabled_without_key() {
+    let (dir, _) = test_project_service();
+    let project_service = ProjectService::new(dir.path().into());
+    let project_info = project_service.project("project1").unwrap();
+
+    assert_eq!(
+        project_service.set_secret(&project_info, "DB_PASSWORD", "hunter2"),
+        Err(ProjectServiceError::SecretsDisabled)
+    );
+    // without secrets there is nothing to decrypt
+    assert_eq!(project_service.materialize_secrets(&project_info), Ok(()));
+}
+
+#[tokio::test]
+async fn invalid_secret_name() {
+    let (_dir, project_service) = test_project_service();
+    let project_info = project_service.project("project1").unwrap();
+
+    for name in ["", "1KEY", "../KEY", "KEY.env"] {
+        assert_eq!(
+            project_service.set_secret(&project_info, name, "value"),
+            Err(ProjectServiceError::InvalidSecretName(name.to_string()))
+        );
+    }
+}
+
+#[tokio::test]
+async fn delete_secret() {
+    let (_dir, project_service) = test_project_service();
+    let project_info = project_service.project("project1").unwrap();
+
+    project_service
+        .set_secret(&project_info, "DB_PASSWORD", "hunter2")
+        .unwrap();
+    project_service
+        .delete_secret(&project_info, "DB_PASSWORD")
+        .unwrap();
+
+    assert!(project_service.secrets(&project_info).unwrap().is_empty());
+    assert_eq!(
+        project_service.delete_secret(&project_info, "DB_PASSWORD"),
+        Err(ProjectServiceError::SecretNotFound {
+            project: "project1".to_string(),
+            name: "DB_PASSWORD".to_string()
+        })
+    );
+}
+
+#[tokio::test]
+async fn secrets_only_exist_while_running() {
+    let (dir, project_service) = test_project_service();
+    let project_service = Arc::new(project_service);
+    let container_service = SecretsContainerService::new(
+        Arc::new(MockContainerService::default()),
+        project_service.clone(),
+    );
+
+    let project_info = project_service.project("project2").unwrap();
+    project_service
+        .set_secret(&project_info, "DB_PASSWORD", "hunter2")
+        .unwrap();
+    project_service
+        .set_secret(&project_info, "GREETING", "say \"hi\"\n")
+        .unwrap();
+
+    let secrets_dir = dir.path().join("project2").join(SECRETS_DIR);
+    assert!(!secrets_dir.exists());
+
+    container_service.start(&project_info).unwrap();
+
+    assert!(container_service.is_online(&project_info).unwrap());
+    assert_eq!(
+        fs::read_to_string(secrets_dir.join("DB_PASSWORD")).unwrap(),
+        "hunter2"
+    );
+    for file in ["DB_PASSWORD", SECRETS_ENV_FILE] {
+        let mode = fs::metadata(secrets_dir.join(file))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    assert_eq!(
+        fs::read_to_string(secrets_dir.join(SECRETS_ENV_FILE)).unwrap(),
+        "DB_PASSWORD=\"hunter2\"\nGREETING=\"say \\\"hi\\\"\\n\"\n"
+    );
+
+    // the decrypted secrets are not reachable through the file api
+    let file = format!("{}/DB_PASSWORD", SECRETS_DIR);
+    assert_eq!(
+        project_service.read_file(&project_info, &file),
+        Err(ProjectServiceError::ReservedFile(file.to_string()))
+    );
+    assert!(
+        !project_service
+            .files(&project_info)
+            .unwrap()
+            .iter()
+            .any(|file| file.starts_with(SECRETS_DIR))
+    );
+
+    container_service.stop(&project_info).unwrap();
+    assert!(!secrets_dir.exists());
+}
+
+#[tokio::test]
+async fn secrets_exist_while_a_stopped_project_is_pulled() {
+    let (dir, project_service) = test_project_service();
+    let project_service = Arc::new(project_service);
+    let mock_container_service = MockContainerService::default();
+    let container_service = SecretsContainerService::new(
+        Arc::new(mock_container_service.clone()),
+        project_service.clone(),
+    );
+
+    let project_info = project_service.project("project2").unwrap();
+    project_service
+        .set_secret(&project_info, "DB_PASSWORD", "hunter2")
+        .unwrap();
+
+    container_service.pull(&project_info).unwrap();
+
+    assert_eq!(
+        *mock_container_service.pulls.lock().unwrap(),
+        vec![("project2".to_string(), true)]
+    );
+    assert!(!dir.path().join("project2").join(SECRETS_DIR).exists());
+
+    // a running project keeps its secrets
+    container_service.start(&project_info).unwrap();
+    container_service.pull(&project_info).unwrap();
+    assert!(dir.path().join("project2").join(SECRETS_DIR).exists());
+
+    container_service
+        .down(&project_info, &DownOptions::default())
+        .unwrap();
+    assert!(!dir.path().join("project2").join(SECRETS_DIR).exists());
+}
+
+#[tokio::test]
+async fn start_fails_with_wrong_key() {
+    let (dir, project_service) = test_project_service();
+    let project_info = project_service.project("project2").unwrap();
+    project_service
+        .set_secret(&project_info, "DB_PASSWORD", "hunter2")
+        .unwrap();
+
+    let project_service = Arc::new(
+        ProjectService::new(dir.path().into()).with_secrets_key(SecretsKey::new("other key")),
+    );
+    let container_service =
+        SecretsContainerService::new(Arc::new(MockContainerService::default()), project_service);
+
+    assert_eq!(
+        container_service.start(&project_info),
+        Err(ContainerServiceError::FailedToProvideSecrets {
+            project: "project2".to_string(),
+            error: ProjectServiceError::FailedToDecryptSecret("DB_PASSWORD".to_string())
+                .to_string()
+        })
+    );
+    assert!(!container_service.is_online(&project_info).unwrap());
+    assert!(!dir.path().join("project2").join(SECRETS_DIR).exists());
+}
+
+#[tokio::test]
+async fn export_leaves_out_decrypted_secrets() {
+    let (_dir, project_service) = test_project_service();
+    let project_info = project_service.project("project1").unwrap();
+    project_service
+        .set_secret(&project_info, "DB_PASSWORD", "hunter2")
+        .unwrap();
+    project_service.materialize_secrets(&project_info).unwrap();
+
+    let mut archive = Vec::new();
+    project_service
+        .export(&project_info, false, &mut archive)
+        .unwrap();
+
+    let mut entries = tar::Archive::new(flate2::read::GzDecoder::new(archive.as_slice()));
+    let paths: Vec<String> = entries
+        .entries()
+        .unwrap()
+        .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().to_string())
+        .collect();
+
+    assert!(paths.contains(&SETTINGS_FILE.to_string()));
+    assert!(!paths.iter().any(|path| path.starts_with(SECRETS_DIR)));
+}
+
+#[test]
+fn keys_are_salted_per_value() {
+    let key = SecretsKey::new("test key");
+
+    let first = key.encrypt("DB_PASSWORD", "hunter2").unwrap();
+    let second = key.encrypt("DB_PASSWORD", "hunter2").unwrap();
+    // every value has its own salt, stored in front of it
+    let salt = |value: &str| STANDARD.decode(value).unwrap()[..SALT_LEN].to_vec();
+    assert_ne!(salt(&first), salt(&second));
+
+    // a fresh key with the same passphrase derives the same keys from the stored salts
+    let key = SecretsKey::new("test key");
+    assert_eq!(key.decrypt("DB_PASSWORD", &first).unwrap(), "hunter2");
+    assert_eq!(key.decrypt("DB_PASSWORD", &second).unwrap(), "hunter2");
+    assert_eq!(
+        key.decrypt("OTHER", &first),
+        Err(ProjectServiceError::FailedToDecryptSecret(
+            "OTHER".to_string()
+        ))
+    );
+}
@@ -1,5 +1,24 @@
-use backend::services::project::{ProjectInfo, ProjectServiceError, ProjectServiceTrait};
+use backend::services::project::service::ProjectService;
+use backend::services::{
+    notification::{NotificationChannel, NotificationSettings},
+    project::{
+        FileFormat, FileMetadata, FileTreeEntry, FileType, NameConflict, ProjectInfo,
+        ProjectServiceError, ProjectServiceTrait, ProjectSettings, SETTINGS_FILE,
+        archive::MAX_ENTRIES,
+        dependencies,
+        env::{EnvEntry, EnvFile},
+        revision,
+        trash::TRASH_DIR,
+    },
+};
+use chrono::TimeDelta;
 use common::project_service::test_project_service;
+use flate2::{Compression, write::GzEncoder};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{PermissionsExt, symlink};
+use tempfile::TempDir;
 
 mod common;
 
@@ -68,7 +87,14 @@ async fn get_project_files() {
 
     files.sort();
 
-    assert_eq!(files, vec![".env".to_string(), "compose.yml".to_string()])
+    assert_eq!(
+        files,
+        vec![
+            ".env".to_string(),
+            "compose.yml".to_string(),
+            "sub/text.txt".to_string()
+        ]
+    )
 }
 
 #[tokio::test]
@@ -88,12 +114,12 @@ async fn read_project_file_not_base() {
     let (_dir, project_service) = test_project_service();
 
     let project_info = project_service.project("project1").unwrap();
-    let error = project_service.read_file(&project_info, "sub/text.txt");
+    let error = project_service.read_file(&project_info, "/etc/passwd");
 
     assert_eq!(
         error,
         Err(ProjectServiceError::InvalidFilePath(
-            "sub/text.txt".to_string()
+            "/etc/passwd".to_string()
         ))
     );
 
@@ -142,12 +168,13 @@ async fn update_project_file_not_base() {
     let (_dir, project_service) = test_project_service();
 
     let project_info = project_service.project("project1").unwrap();
-    let error = project_service.update_file(&project_info, "sub/text.txt", "content");
+    let error =
+        project_service.update_file(&project_info, "sub/../../project3/compose.yml", "content");
 
     assert_eq!(
         error,
         Err(ProjectServiceError::InvalidFilePath(
-            "sub/text.txt".to_string()
+            "sub/../../project3/compose.yml".to_string()
         ))
     );
 
@@ -181,6 +208,500 @@ async fn create_new_project() {
     assert_eq!(files, Ok(vec!["compose.yml".to_string()]));
 }
 
+#[tokio::test]
+async fn create_project_from_template() {
+    let (dir, project_service) = test_project_service();
+
+    let variables = HashMap::from([
+        ("POSTGRES_PASSWORD".to_string(), "secret".to_string()),
+        ("PORT".to_string(), "5433".to_string()),
+    ]);
+    let project_info = project_service
+        .create_from_template("db", "postgres", &variables)
+        .unwrap();
+
+    assert_eq!(project_info.dir, dir.path().join("db"));
+    assert_eq!(
+        project_service.read_file(&project_info, ".env"),
+        Ok("POSTGRES_USER=postgres\nPOSTGRES_PASSWORD=secret\nPOSTGRES_DB=postgres\n".to_string())
+    );
+    let compose = project_service
+        .read_file(&project_info, "compose.yml")
+        .unwrap();
+    assert!(compose.contains("image: postgres:17"));
+    assert!(compose.contains("\"5433:5432\""));
+}
+
+#[tokio::test]
+async fn create_project_from_template_fails() {
+    let (dir, project_service) = test_project_service();
+
+    assert_eq!(
+        project_service.create_from_template("db", "postgres", &HashMap::new()),
+        Err(ProjectServiceError::MissingTemplateParameter {
+            template: "postgres".to_string(),
+            parameter: "POSTGRES_PASSWORD".to_string()
+        })
+    );
+    assert_eq!(
+        project_service.create_from_template("db", "unknown", &HashMap::new()),
+        Err(ProjectServiceError::TemplateNotFound("unknown".to_string()))
+    );
+    assert!(!dir.path().join("db").exists());
+}
+
+#[tokio::test]
+async fn user_defined_templates() {
+    let (dir, project_service) = test_project_service();
+    let templates = TempDir::new().unwrap();
+    let project_service = project_service.with_template_dir(templates.path().into());
+
+    let app = templates.path().join("app");
+    std::fs::create_dir_all(app.join("config")).unwrap();
+    std::fs::write(
+        app.join("template.json"),
+        r#"{"description": "My app", "parameters": [{"name": "DOMAIN", "default": "localhost"}]}"#,
+    )
+    .unwrap();
+    std::fs::write(app.join("compose.yml"), "image: app").unwrap();
+    std::fs::write(app.join("config/app.conf"), "domain={{DOMAIN}}").unwrap();
+
+    let redis = templates.path().join("redis");
+    std::fs::create_dir(&redis).unwrap();
+    std::fs::write(redis.join("compose.yml"), "image: my-redis").unwrap();
+
+    let names: Vec<_> = project_service
+        .templates()
+        .unwrap()
+        .into_iter()
+        .map(|template| template.name)
+        .collect();
+    assert_eq!(names, vec!["postgres", "nginx", "app", "redis"]);
+
+    let project_info = project_service
+        .create_from_template("myApp", "app", &HashMap::new())
+        .unwrap();
+
+    assert_eq!(
+        project_service.files(&project_info),
+        Ok(vec![
+            "compose.yml".to_string(),
+            "config/app.conf".to_string()
+        ])
+    );
+    assert_eq!(
+        project_service.read_file(&project_info, "config/app.conf"),
+        Ok("domain=localhost".to_string())
+    );
+    assert!(dir.path().join("myApp").exists());
+}
+
+fn archive(entries: &[(&str, tar::EntryType, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+    for (path, kind, content) in entries {
+        let mut header = tar::Header::new_gnu();
+        // written directly, `set_path` refuses paths with `..`
+        header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+        header.set_entry_type(*kind);
+        header.set_mode(0o644);
+        if *kind == tar::EntryType::Symlink {
+            header.set_link_name(content).unwrap();
+            header.set_size(0);
+            header.set_cksum();
+            builder.append(&header, std::io::empty()).unwrap();
+        } else {
+            header.set_size(content.len() as u64);
+            header.set_cksum();
+            builder.append(&header, content.as_bytes()).unwrap();
+        }
+    }
+
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+#[tokio::test]
+async fn export_and_import_project() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    symlink("sub/text.txt", project_info.dir.join("link.txt")).unwrap();
+
+    let mut exported = Vec::new();
+    project_service
+        .export(&project_info, false, &mut exported)
+        .unwrap();
+
+    let imported = project_service
+        .import("imported", NameConflict::Fail, &mut exported.as_slice())
+        .unwrap();
+
+    assert_eq!(imported.name, "imported");
+    assert_eq!(
+        project_service.files(&imported),
+        project_service.files(&project_info)
+    );
+    assert_eq!(
+        project_service.read_file(&imported, "sub/text.txt"),
+        Ok("sub file".to_string())
+    );
+    assert_eq!(
+        std::fs::read_link(imported.dir.join("link.txt")).unwrap(),
+        std::path::PathBuf::from("sub/text.txt")
+    );
+
+    std::fs::write(project_info.dir.join(".env.production"), "KEY=value").unwrap();
+
+    let mut exported = Vec::new();
+    project_service
+        .export(&project_info, true, &mut exported)
+        .unwrap();
+    let imported = project_service
+        .import("withoutEnv", NameConflict::Fail, &mut exported.as_slice())
+        .unwrap();
+
+    assert!(!imported.dir.join(".env").exists());
+    assert!(!imported.dir.join(".env.production").exists());
+    assert!(imported.dir.join("compose.yml").exists());
+}
+
+#[tokio::test]
+async fn export_without_env_leaves_out_settings() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    let settings = ProjectSettings {
+        auto_update: true,
+        ..Default::default()
+    };
+    project_service
+        .update_settings(&project_info, &settings)
+        .unwrap();
+
+    let mut exported = Vec::new();
+    project_service
+        .export(&project_info, false, &mut exported)
+        .unwrap();
+    let imported = project_service
+        .import("withSettings", NameConflict::Fail, &mut exported.as_slice())
+        .unwrap();
+    assert!(project_service.settings(&imported).unwrap().auto_update);
+
+    let mut exported = Vec::new();
+    project_service
+        .export(&project_info, true, &mut exported)
+        .unwrap();
+    let imported = project_service
+        .import(
+            "withoutSettings",
+            NameConflict::Fail,
+            &mut exported.as_slice(),
+        )
+        .unwrap();
+    assert!(!imported.dir.join(SETTINGS_FILE).exists());
+}
+
+#[tokio::test]
+async fn import_limits_unpacked_content() {
+    let dir = TempDir::new().unwrap();
+    let project_service = ProjectService::new(dir.path().into()).with_max_file_size(4);
+
+    let files: Vec<String> = (0..5).map(|number| format!("file{}", number)).collect();
+    let too_large: Vec<_> = files
+        .iter()
+        .map(|file| (file.as_str(), tar::EntryType::Regular, "1234"))
+        .collect();
+    let content = archive(&too_large);
+    let error = project_service.import("large", NameConflict::Fail, &mut content.as_slice());
+
+    assert!(
+        matches!(error, Err(ProjectServiceError::InvalidArchive(_))),
+        "{:?}",
+        error
+    );
+    assert!(!dir.path().join("large").exists());
+
+    let content = archive(&too_large[..4]);
+    assert!(
+        project_service
+            .import("fits", NameConflict::Fail, &mut content.as_slice())
+            .is_ok()
+    );
+
+    let names: Vec<String> = (0..=MAX_ENTRIES)
+        .map(|number| format!("d{}", number))
+        .collect();
+    let too_many: Vec<_> = names
+        .iter()
+        .map(|name| (name.as_str(), tar::EntryType::Directory, ""))
+        .collect();
+    let content = archive(&too_many);
+    let error = project_service.import("many", NameConflict::Fail, &mut content.as_slice());
+
+    assert!(
+        matches!(error, Err(ProjectServiceError::InvalidArchive(_))),
+        "{:?}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn import_name_conflict() {
+    let (_dir, project_service) = test_project_service();
+    let content = archive(&[("compose.yml", tar::EntryType::Regular, "services:")]);
+
+    assert_eq!(
+        project_service.import("project1", NameConflict::Fail, &mut content.as_slice()),
+        Err(ProjectServiceError::ProjectAlreadyExists(
+            "project1".to_string()
+        ))
+    );
+
+    let first = project_service
+        .import("project1", NameConflict::Rename, &mut content.as_slice())
+        .unwrap();
+    let second = project_service
+        .import("project1", NameConflict::Rename, &mut content.as_slice())
+        .unwrap();
+
+    assert_eq!(first.name, "project1-2");
+    assert_eq!(second.name, "project1-3");
+    assert_eq!(
+        project_service.read_file(&second, "compose.yml"),
+        Ok("services:".to_string())
+    );
+}
+
+#[tokio::test]
+async fn import_rejects_escaping_entries() {
+    let (dir, project_service) = test_project_service();
+
+    let archives = [
+        archive(&[("../escape.txt", tar::EntryType::Regular, "escape")]),
+        archive(&[("/tmp/escape.txt", tar::EntryType::Regular, "escape")]),
+        archive(&[("link", tar::EntryType::Symlink, "../../etc")]),
+        archive(&[("sub/link", tar::EntryType::Symlink, "/etc/passwd")]),
+        archive(&[("hard", tar::EntryType::Link, "compose.yml")]),
+        b"no archive".to_vec(),
+    ];
+
+    for content in archives {
+        let error = project_service.import("evil", NameConflict::Fail, &mut content.as_slice());
+
+        assert!(
+            matches!(error, Err(ProjectServiceError::InvalidArchive(_))),
+            "{:?}",
+            error
+        );
+        assert!(!dir.path().join("evil").exists());
+    }
+    assert!(!dir.path().join("escape.txt").exists());
+}
+
+#[tokio::test]
+async fn read_env_file() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    project_service
+        .update_file(
+            &project_info,
+            ".env",
+            "# database\nDB_USER=app\nexport DB_PASSWORD=\"se\\\"cret\" # inline\n\nTZ=Europe/Berlin # zone\nUNUSED='a b'\n",
+        )
+        .unwrap();
+    project_service
+        .update_file(
+            &project_info,
+            "compose.yml",
+            "services:\n  db:\n    image: postgres:${PG_VERSION:-17}\n    user: $DB_USER\n    environment:\n      - PASSWORD=${DB_PASSWORD}\n      - HOST=${DB_HOST:?required}\n      - PRICE=$$5\n      - TZ=${TZ}\n",
+        )
+        .unwrap();
+
+    let env = project_service.env(&project_info, ".env").unwrap();
+
+    assert_eq!(
+        env,
+        EnvFile {
+            entries: vec![
+                env_entry("DB_USER", "app"),
+                env_entry("DB_PASSWORD", "se\"cret"),
+                env_entry("TZ", "Europe/Berlin"),
+                env_entry("UNUSED", "a b"),
+            ],
+            missing: vec!["DB_HOST".to_string()],
+            unused: vec!["UNUSED".to_string()],
+        }
+    );
+
+    // all keys are passed to the container
+    project_service
+        .update_file(
+            &project_info,
+            "compose.override.yml",
+            "services:\n  db:\n    env_file:\n      - path: ./.env\n",
+        )
+        .unwrap();
+
+    let env = project_service.env(&project_info, ".env").unwrap();
+
+    assert_eq!(env.unused, Vec::<String>::new());
+    assert_eq!(
+        project_service.env(&project_info, "missing.env"),
+        Ok(EnvFile::default())
+    );
+}
+
+fn env_entry(key: &str, value: &str) -> EnvEntry {
+    EnvEntry {
+        key: key.to_string(),
+        value: value.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn update_env_file() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    project_service
+        .update_file(
+            &project_info,
+            ".env",
+            "# database\nDB_USER=app # user\nDB_PASSWORD=old\n\n# removed\nOLD=1\n",
+        )
+        .unwrap();
+
+    let env = project_service
+        .update_env(
+            &project_info,
+            ".env",
+            &[
+                env_entry("DB_USER", "app"),
+                env_entry("DB_PASSWORD", "new secret"),
+                env_entry("QUOTE", "it's"),
+                env_entry("EMPTY", ""),
+            ],
+        )
+        .unwrap();
+
+    assert_eq!(
+        project_service.read_file(&project_info, ".env"),
+        Ok("# database\nDB_USER=app # user\nDB_PASSWORD='new secret'\n\n# removed\nQUOTE=\"it's\"\nEMPTY=\n".to_string())
+    );
+    assert_eq!(
+        env.entries,
+        vec![
+            env_entry("DB_USER", "app"),
+            env_entry("DB_PASSWORD", "new secret"),
+            env_entry("QUOTE", "it's"),
+            env_entry("EMPTY", ""),
+        ]
+    );
+
+    assert_eq!(
+        project_service.update_env(&project_info, ".env", &[env_entry("NO-KEY", "1")]),
+        Err(ProjectServiceError::InvalidEnvKey("NO-KEY".to_string()))
+    );
+    assert_eq!(
+        project_service.update_env(
+            &project_info,
+            ".env",
+            &[env_entry("KEY", "1"), env_entry("KEY", "2")]
+        ),
+        Err(ProjectServiceError::DuplicateEnvKey("KEY".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn rename_project() {
+    let (dir, project_service) = test_project_service();
+
+    let project2 = project_service.project("project2").unwrap();
+    let mut settings = project_service.settings(&project2).unwrap();
+    settings.dependencies = vec!["project1".to_string(), "project3".to_string()];
+    project_service
+        .update_settings(&project2, &settings)
+        .unwrap();
+
+    let project_info = project_service.project("project1").unwrap();
+    let renamed = project_service.rename(&project_info, "renamed");
+
+    assert_eq!(
+        renamed,
+        Ok(ProjectInfo {
+            name: "renamed".to_string(),
+            dir: dir.path().join("renamed"),
+        })
+    );
+    assert!(!dir.path().join("project1").exists());
+    assert_eq!(
+        project_service.read_file(&renamed.unwrap(), "sub/text.txt"),
+        Ok("sub file".to_string())
+    );
+    assert_eq!(
+        project_service.settings(&project2).unwrap().dependencies,
+        vec!["renamed".to_string(), "project3".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn rename_project_conflicts() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+
+    assert_eq!(
+        project_service.rename(&project_info, "project2"),
+        Err(ProjectServiceError::ProjectAlreadyExists(
+            "project2".to_string()
+        ))
+    );
+    assert_eq!(
+        project_service.rename(&project_info, "../renamed"),
+        Err(ProjectServiceError::InvalidName("../renamed".to_string()))
+    );
+    assert_eq!(
+        project_service.duplicate(&project_info, "project3"),
+        Err(ProjectServiceError::ProjectAlreadyExists(
+            "project3".to_string()
+        ))
+    );
+}
+
+#[tokio::test]
+async fn duplicate_project() {
+    let (dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    let mut settings = project_service.settings(&project_info).unwrap();
+    settings.dependencies = vec!["project2".to_string()];
+    project_service
+        .update_settings(&project_info, &settings)
+        .unwrap();
+    symlink("sub/text.txt", project_info.dir.join("link.txt")).unwrap();
+
+    let copy = project_service.duplicate(&project_info, "copy").unwrap();
+
+    assert_eq!(copy.dir, dir.path().join("copy"));
+    assert_eq!(
+        project_service.files(&copy),
+        project_service.files(&project_info)
+    );
+    assert_eq!(
+        std::fs::read_link(copy.dir.join("link.txt")).unwrap(),
+        std::path::PathBuf::from("sub/text.txt")
+    );
+    assert_eq!(
+        project_service.settings(&copy),
+        Ok(ProjectSettings::default())
+    );
+    assert_eq!(
+        project_service.read_file(&project_info, "sub/text.txt"),
+        Ok("sub file".to_string())
+    );
+}
+
 #[tokio::test]
 async fn create_already_existing_project() {
     let (_dir, project_service) = test_project_service();
@@ -203,7 +724,7 @@ async fn create_invalid_project() {
 
     assert_eq!(
         error,
-        Err(ProjectServiceError::InvalidFilePath(
+        Err(ProjectServiceError::InvalidName(
             "test/newProject".to_string()
         ))
     );
@@ -212,7 +733,7 @@ async fn create_invalid_project() {
 
     assert_eq!(
         error,
-        Err(ProjectServiceError::InvalidFilePath(
+        Err(ProjectServiceError::InvalidName(
             "../newProject".to_string()
         ))
     );
@@ -277,3 +798,783 @@ async fn delete_project() {
         ])
     )
 }
+
+#[tokio::test]
+async fn restore_deleted_project() {
+    let (dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    project_service.delete(&project_info).unwrap();
+
+    let trash = project_service.trash().unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].project, "project1");
+    assert_eq!(trash[0].file, None);
+    assert_eq!(trash[0].kind, FileType::Directory);
+    assert_eq!(
+        project_service.project(TRASH_DIR),
+        Err(ProjectServiceError::InvalidName(TRASH_DIR.to_string()))
+    );
+
+    let restored = project_service.restore(&trash[0].id);
+
+    assert_eq!(restored, Ok(trash[0].clone()));
+    assert_eq!(
+        project_service.read_file(&project_info, "sub/text.txt"),
+        Ok("sub file".to_string())
+    );
+    assert_eq!(project_service.trash(), Ok(vec![]));
+    assert!(dir.path().join("project1").exists());
+}
+
+#[tokio::test]
+async fn restore_deleted_files() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    project_service
+        .delete_file(&project_info, "compose.yml")
+        .unwrap();
+    project_service.delete_dir(&project_info, "sub").unwrap();
+
+    let trash = project_service.trash().unwrap();
+    assert_eq!(trash[0].file, Some("sub".to_string()));
+    assert_eq!(trash[0].kind, FileType::Directory);
+    assert_eq!(trash[1].file, Some("compose.yml".to_string()));
+    assert_eq!(trash[1].kind, FileType::File);
+
+    project_service
+        .update_file(&project_info, "compose.yml", "new")
+        .unwrap();
+
+    assert_eq!(
+        project_service.restore(&trash[1].id),
+        Err(ProjectServiceError::FileAlreadyExists {
+            project: "project1".to_string(),
+            file: "compose.yml".to_string()
+        })
+    );
+
+    project_service.restore(&trash[0].id).unwrap();
+    assert_eq!(
+        project_service.read_file(&project_info, "sub/text.txt"),
+        Ok("sub file".to_string())
+    );
+
+    assert_eq!(project_service.purge(&trash[1].id), Ok(trash[1].clone()));
+    assert_eq!(project_service.trash(), Ok(vec![]));
+    assert_eq!(
+        project_service.purge("../project2"),
+        Err(ProjectServiceError::TrashEntryNotFound(
+            "../project2".to_string()
+        ))
+    );
+    assert!(project_service.project("project2").is_ok());
+}
+
+#[tokio::test]
+async fn purge_expired_trash() {
+    let (dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project2").unwrap();
+    project_service.delete(&project_info).unwrap();
+
+    assert_eq!(project_service.purge_expired(), Ok(vec![]));
+    assert_eq!(project_service.trash().unwrap().len(), 1);
+
+    let project_service =
+        ProjectService::new(dir.path().into()).with_trash_retention(Some(TimeDelta::zero()));
+    let purged = project_service.purge_expired().unwrap();
+
+    assert_eq!(purged.len(), 1);
+    assert_eq!(purged[0].project, "project2");
+    assert_eq!(project_service.trash(), Ok(vec![]));
+}
+
+#[tokio::test]
+async fn default_project_settings() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    let settings = project_service.settings(&project_info);
+
+    assert_eq!(settings, Ok(ProjectSettings::default()));
+}
+
+#[tokio::test]
+async fn update_project_settings() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    let settings = ProjectSettings {
+        notifications: NotificationSettings {
+            channels: vec![NotificationChannel::Webhook {
+                url: "http://localhost/hook".to_string(),
+            }],
+            events: vec![],
+        },
+        ..Default::default()
+    };
+
+    project_service
+        .update_settings(&project_info, &settings)
+        .unwrap();
+
+    assert_eq!(project_service.settings(&project_info), Ok(settings));
+
+    let files = project_service.files(&project_info);
+    assert_eq!(
+        files,
+        Ok(vec![
+            ".env".to_string(),
+            "compose.yml".to_string(),
+            "sub/text.txt".to_string()
+        ])
+    );
+}
+
+#[tokio::test]
+async fn settings_file_is_reserved() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    let error = project_service.read_file(&project_info, ".containeryard.json");
+
+    assert_eq!(
+        error,
+        Err(ProjectServiceError::ReservedFile(
+            ".containeryard.json".to_string()
+        ))
+    );
+
+    let error = project_service.update_file(&project_info, ".containeryard.json", "{}");
+
+    assert_eq!(
+        error,
+        Err(ProjectServiceError::ReservedFile(
+            ".containeryard.json".to_string()
+        ))
+    );
+}
+
+fn set_dependencies(
+    project_service: &dyn ProjectServiceTrait,
+    project: &str,
+    dependencies: &[&str],
+) {
+    let project_info = project_service.project(project).unwrap();
+    let settings = ProjectSettings {
+        dependencies: dependencies.iter().map(|name| name.to_string()).collect(),
+        ..Default::default()
+    };
+
+    project_service
+        .update_settings(&project_info, &settings)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn dependency_start_order() {
+    let (_dir, project_service) = test_project_service();
+    set_dependencies(&project_service, "project1", &["project2"]);
+    set_dependencies(&project_service, "project2", &["project3"]);
+
+    let project1 = project_service.project("project1").unwrap();
+    let order = dependencies::start_order(&project_service, &[project1]).unwrap();
+    let names: Vec<_> = order.iter().map(|project| project.name.as_str()).collect();
+
+    assert_eq!(names, vec!["project3", "project2", "project1"]);
+}
+
+#[tokio::test]
+async fn dependency_cycle() {
+    let (_dir, project_service) = test_project_service();
+    set_dependencies(&project_service, "project1", &["project2"]);
+
+    let project2 = project_service.project("project2").unwrap();
+    let error =
+        dependencies::validate_dependencies(&project_service, &project2, &["project1".to_string()]);
+
+    assert_eq!(
+        error,
+        Err(ProjectServiceError::DependencyCycle(
+            "project2 -> project1 -> project2".to_string()
+        ))
+    );
+}
+
+#[tokio::test]
+async fn unknown_dependency() {
+    let (_dir, project_service) = test_project_service();
+
+    let project1 = project_service.project("project1").unwrap();
+    let error = dependencies::validate_dependencies(
+        &project_service,
+        &project1,
+        &["project404".to_string()],
+    );
+
+    assert_eq!(
+        error,
+        Err(ProjectServiceError::DependencyNotFound {
+            project: "project1".to_string(),
+            dependency: "project404".to_string()
+        })
+    );
+}
+
+#[tokio::test]
+async fn dependents_in_stop_order() {
+    let (_dir, project_service) = test_project_service();
+    set_dependencies(&project_service, "project1", &["project2"]);
+    set_dependencies(&project_service, "project3", &["project1"]);
+
+    let project2 = project_service.project("project2").unwrap();
+    let dependents = dependencies::dependents(&project_service, &project2).unwrap();
+    let names: Vec<_> = dependents
+        .iter()
+        .map(|project| project.name.as_str())
+        .collect();
+
+    assert_eq!(names, vec!["project3", "project1"]);
+}
+
+#[tokio::test]
+async fn dependency_start_levels() {
+    let (_dir, project_service) = test_project_service();
+    set_dependencies(&project_service, "project1", &["project2"]);
+
+    let projects = project_service.all_projects().unwrap();
+    let levels = dependencies::start_levels(&project_service, &projects).unwrap();
+    let names: Vec<Vec<_>> = levels
+        .iter()
+        .map(|level| level.iter().map(|project| project.name.as_str()).collect())
+        .collect();
+
+    assert_eq!(names, vec![vec!["project2", "project3"], vec!["project1"]]);
+}
+
+#[tokio::test]
+async fn project_name_pattern() {
+    let (_dir, project_service) = test_project_service();
+    let project1 = project_service.project("project1").unwrap();
+
+    assert!(project1.matches("project1"));
+    assert!(project1.matches("proj*"));
+    assert!(project1.matches("*1"));
+    assert!(project1.matches("project?"));
+    assert!(!project1.matches("project"));
+    assert!(!project1.matches("*2"));
+}
+
+#[tokio::test]
+async fn read_nested_project_file() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    let content = project_service.read_file(&project_info, "sub/text.txt");
+
+    assert_eq!(content, Ok("sub file".to_string()));
+
+    let error = project_service.read_file(&project_info, "sub");
+
+    assert_eq!(error, Err(ProjectServiceError::NotAFile("sub".to_string())));
+}
+
+#[tokio::test]
+async fn update_nested_project_file() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project2").unwrap();
+    project_service
+        .update_file(&project_info, "nginx/conf.d/default.conf", "server {}")
+        .unwrap();
+
+    let content = project_service.read_file(&project_info, "nginx/conf.d/default.conf");
+    assert_eq!(content, Ok("server {}".to_string()));
+}
+
+#[tokio::test]
+async fn get_project_tree() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    let tree = project_service.tree(&project_info);
+
+    assert_eq!(
+        tree,
+        Ok(vec![
+            FileTreeEntry {
+                name: ".env".to_string(),
+                path: ".env".to_string(),
+                kind: FileType::File,
+                binary: false,
+                children: vec![],
+            },
+            FileTreeEntry {
+                name: "compose.yml".to_string(),
+                path: "compose.yml".to_string(),
+                kind: FileType::File,
+                binary: false,
+                children: vec![],
+            },
+            FileTreeEntry {
+                name: "sub".to_string(),
+                path: "sub".to_string(),
+                kind: FileType::Directory,
+                binary: false,
+                children: vec![FileTreeEntry {
+                    name: "text.txt".to_string(),
+                    path: "sub/text.txt".to_string(),
+                    kind: FileType::File,
+                    binary: false,
+                    children: vec![],
+                }],
+            },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn project_tree_leaves_out_non_utf8_names() {
+    let (dir, project_service) = test_project_service();
+    let sub = dir.path().join("project1").join("sub");
+    std::fs::write(sub.join(OsStr::from_bytes(b"invalid-\xff.txt")), "").unwrap();
+
+    let project_info = project_service.project("project1").unwrap();
+
+    assert_eq!(
+        project_service.files(&project_info),
+        Ok(vec![
+            ".env".to_string(),
+            "compose.yml".to_string(),
+            "sub/text.txt".to_string()
+        ])
+    );
+}
+
+#[tokio::test]
+async fn create_and_delete_project_dir() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project2").unwrap();
+    project_service
+        .create_dir(&project_info, "nginx/conf.d")
+        .unwrap();
+
+    assert!(project_info.dir.join("nginx/conf.d").is_dir());
+
+    project_service.delete_dir(&project_info, "nginx").unwrap();
+
+    assert!(!project_info.dir.join("nginx").exists());
+
+    let error = project_service.delete_dir(&project_info, "nginx");
+
+    assert_eq!(
+        error,
+        Err(ProjectServiceError::FileNotFound {
+            project: "project2".to_string(),
+            file: "nginx".to_string()
+        })
+    );
+
+    std::fs::write(project_info.dir.join("compose.yml"), "").unwrap();
+    let error = project_service.delete_dir(&project_info, "compose.yml");
+
+    assert_eq!(
+        error,
+        Err(ProjectServiceError::NotADirectory(
+            "compose.yml".to_string()
+        ))
+    );
+
+    let error = project_service.delete_dir(&project_info, "..");
+
+    assert_eq!(
+        error,
+        Err(ProjectServiceError::InvalidFilePath("..".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn invalid_project_names() {
+    let (_dir, project_service) = test_project_service();
+
+    for name in ["..", ".", "", "project1/sub", "/etc"] {
+        assert_eq!(
+            project_service.project(name),
+            Err(ProjectServiceError::InvalidName(name.to_string()))
+        );
+    }
+}
+
+#[tokio::test]
+async fn symlinked_project_outside_base() {
+    let (dir, project_service) = test_project_service();
+    let outside = TempDir::new().unwrap();
+    symlink(outside.path(), dir.path().join("escape")).unwrap();
+
+    assert_eq!(
+        project_service.project("escape"),
+        Err(ProjectServiceError::InvalidName("escape".to_string()))
+    );
+
+    let names: Vec<_> = project_service
+        .all_projects()
+        .unwrap()
+        .into_iter()
+        .map(|project| project.name)
+        .collect();
+    assert_eq!(names, vec!["project1", "project2", "project3"]);
+}
+
+#[tokio::test]
+async fn symlink_escaping_project_dir() {
+    let (_dir, project_service) = test_project_service();
+    let outside = TempDir::new().unwrap();
+    std::fs::write(outside.path().join("secret"), "secret").unwrap();
+
+    let project_info = project_service.project("project1").unwrap();
+    symlink(outside.path(), project_info.dir.join("link")).unwrap();
+    symlink(
+        outside.path().join("secret"),
+        project_info.dir.join("secret"),
+    )
+    .unwrap();
+    symlink(
+        outside.path().join("missing"),
+        project_info.dir.join("dangling"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        project_service.read_file(&project_info, "link/secret"),
+        Err(ProjectServiceError::InvalidFilePath(
+            "link/secret".to_string()
+        ))
+    );
+    assert_eq!(
+        project_service.read_file(&project_info, "secret"),
+        Err(ProjectServiceError::InvalidFilePath("secret".to_string()))
+    );
+    assert_eq!(
+        project_service.update_file(&project_info, "link/new", "content"),
+        Err(ProjectServiceError::InvalidFilePath("link/new".to_string()))
+    );
+    assert_eq!(
+        project_service.update_file(&project_info, "dangling", "content"),
+        Err(ProjectServiceError::InvalidFilePath("dangling".to_string()))
+    );
+    assert_eq!(
+        project_service.delete_dir(&project_info, "link"),
+        Err(ProjectServiceError::InvalidFilePath("link".to_string()))
+    );
+
+    assert!(!outside.path().join("new").exists());
+    assert!(!outside.path().join("missing").exists());
+    assert_eq!(
+        project_service.files(&project_info),
+        Ok(vec![
+            ".env".to_string(),
+            "compose.yml".to_string(),
+            "sub/text.txt".to_string()
+        ])
+    );
+}
+
+#[tokio::test]
+async fn concurrent_settings_changes() {
+    let (_dir, project_service) = test_project_service();
+    let project_info = project_service.project("project1").unwrap();
+
+    std::thread::scope(|scope| {
+        for number in 0..16 {
+            let project_service = &project_service;
+            let project_info = &project_info;
+            scope.spawn(move || {
+                project_service
+                    .modify_settings(project_info, &mut |settings| {
+                        settings.dependencies.push(format!("project{}", number));
+                        Ok(())
+                    })
+                    .unwrap();
+            });
+        }
+    });
+
+    let settings = project_service.settings(&project_info).unwrap();
+    assert_eq!(settings.dependencies.len(), 16);
+}
+
+#[tokio::test]
+async fn symlink_inside_project_dir() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    project_service
+        .update_settings(&project_info, &ProjectSettings::default())
+        .unwrap();
+    symlink(project_info.dir.join("sub"), project_info.dir.join("link")).unwrap();
+    symlink(
+        project_info.dir.join(".containeryard.json"),
+        project_info.dir.join("settings"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        project_service.read_file(&project_info, "link/text.txt"),
+        Ok("sub file".to_string())
+    );
+    assert_eq!(
+        project_service.update_file(&project_info, "settings", "{}"),
+        Err(ProjectServiceError::ReservedFile("settings".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn write_binary_file() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project2").unwrap();
+    project_service
+        .write_bytes(&project_info, "logo.png", &[0x89, b'P', b'N', b'G', 0x00])
+        .unwrap();
+
+    assert_eq!(
+        project_service.read_file(&project_info, "logo.png"),
+        Err(ProjectServiceError::BinaryFile("logo.png".to_string()))
+    );
+    assert_eq!(
+        project_service.tree(&project_info),
+        Ok(vec![
+            FileTreeEntry {
+                name: "compose.yml".to_string(),
+                path: "compose.yml".to_string(),
+                kind: FileType::File,
+                binary: false,
+                children: vec![],
+            },
+            FileTreeEntry {
+                name: "logo.png".to_string(),
+                path: "logo.png".to_string(),
+                kind: FileType::File,
+                binary: true,
+                children: vec![],
+            }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn write_file_too_large() {
+    let dir = TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join("project")).unwrap();
+    let project_service = ProjectService::new(dir.path().into()).with_max_file_size(4);
+
+    let project_info = project_service.project("project").unwrap();
+    let error = project_service.write_bytes(&project_info, "file", b"12345");
+
+    assert_eq!(
+        error,
+        Err(ProjectServiceError::FileTooLarge {
+            file: "file".to_string(),
+            max_size: 4
+        })
+    );
+}
+
+#[tokio::test]
+async fn project_file_metadata() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    let path = project_info.dir.join("compose.yml");
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+    let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+
+    let metadata = project_service.file_metadata(&project_info, "compose.yml");
+
+    assert_eq!(
+        metadata,
+        Ok(FileMetadata {
+            path: "compose.yml".to_string(),
+            size: 11,
+            modified: modified.into(),
+            mode: "640".to_string(),
+            format: FileFormat::Compose,
+            hash: Some(
+                "3493e6b5ddf34891e572f911db893efd9e46af828e011ea778a7c1eb64763588".to_string()
+            ),
+        })
+    );
+
+    let error = project_service.file_metadata(&project_info, "sub");
+
+    assert_eq!(error, Err(ProjectServiceError::NotAFile("sub".to_string())));
+}
+
+#[tokio::test]
+async fn large_files_are_not_hashed() {
+    let (_dir, project_service) = test_project_service();
+    let project_service = project_service.with_max_file_size(5);
+
+    let project_info = project_service.project("project1").unwrap();
+    let metadata = project_service
+        .file_metadata(&project_info, "compose.yml")
+        .unwrap();
+
+    assert_eq!(metadata.size, 11);
+    assert_eq!(metadata.format, FileFormat::Compose);
+    assert_eq!(metadata.hash, None);
+}
+
+#[tokio::test]
+async fn detect_file_format() {
+    assert_eq!(FileFormat::detect("compose.yml", b""), FileFormat::Compose);
+    assert_eq!(
+        FileFormat::detect("docker-compose.override.yaml", b""),
+        FileFormat::Compose
+    );
+    assert_eq!(FileFormat::detect(".env", b""), FileFormat::Env);
+    assert_eq!(FileFormat::detect(".env.production", b""), FileFormat::Env);
+    assert_eq!(FileFormat::detect("app.env", b""), FileFormat::Env);
+    assert_eq!(
+        FileFormat::detect("nginx.conf", b"server {}"),
+        FileFormat::Text
+    );
+    assert_eq!(
+        FileFormat::detect("README", "äöü".as_bytes()),
+        FileFormat::Text
+    );
+    assert_eq!(
+        FileFormat::detect("logo.png", &[0x89, 0x00]),
+        FileFormat::Binary
+    );
+    assert_eq!(
+        FileFormat::detect("key.der", &[0xff, 0xfe]),
+        FileFormat::Binary
+    );
+}
+
+#[tokio::test]
+async fn update_file_if_match() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    let current = revision(b"compose.yml");
+
+    let content = project_service.update_file_if_match(
+        &project_info,
+        "compose.yml",
+        "newCompose",
+        std::slice::from_ref(&current),
+    );
+    assert_eq!(content, Ok("newCompose".to_string()));
+
+    let error = project_service.update_file_if_match(
+        &project_info,
+        "compose.yml",
+        "otherCompose",
+        &[current],
+    );
+    assert_eq!(
+        error,
+        Err(ProjectServiceError::FileChanged {
+            file: "compose.yml".to_string(),
+            revision: Some(revision(b"newCompose")),
+            content: Some("newCompose".to_string()),
+        })
+    );
+}
+
+#[tokio::test]
+async fn update_file_if_match_external_change() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    let current = revision(b".env");
+    std::fs::write(project_info.dir.join(".env"), "CHANGED=1").unwrap();
+
+    let error = project_service.update_file_if_match(&project_info, ".env", "A=1", &[current]);
+    assert_eq!(
+        error,
+        Err(ProjectServiceError::FileChanged {
+            file: ".env".to_string(),
+            revision: Some(revision(b"CHANGED=1")),
+            content: Some("CHANGED=1".to_string()),
+        })
+    );
+
+    let error =
+        project_service.update_file_if_match(&project_info, "new", "content", &["*".to_string()]);
+    assert_eq!(
+        error,
+        Err(ProjectServiceError::FileChanged {
+            file: "new".to_string(),
+            revision: None,
+            content: None,
+        })
+    );
+}
+
+#[tokio::test]
+async fn update_file_keeps_permissions() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    let path = project_info.dir.join(".env");
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+    project_service
+        .update_file(&project_info, ".env", "SECRET=1")
+        .unwrap();
+
+    let metadata = std::fs::metadata(&path).unwrap();
+    assert_eq!(metadata.permissions().mode() & 0o7777, 0o600);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "SECRET=1");
+}
+
+#[tokio::test]
+async fn failed_write_keeps_original() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+
+    // a file can't replace the directory, so the final rename fails
+    let error = project_service.update_file(&project_info, "sub", "content");
+
+    assert!(matches!(error, Err(ProjectServiceError::NotAFile(_))));
+    assert_eq!(
+        project_service.read_file(&project_info, "sub/text.txt"),
+        Ok("sub file".to_string())
+    );
+
+    let leftovers: Vec<_> = std::fs::read_dir(&project_info.dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert_eq!(leftovers, Vec::<std::ffi::OsString>::new());
+}
+
+#[tokio::test]
+async fn update_file_through_symlink() {
+    let (_dir, project_service) = test_project_service();
+
+    let project_info = project_service.project("project1").unwrap();
+    symlink(
+        project_info.dir.join("compose.yml"),
+        project_info.dir.join("docker-compose.yml"),
+    )
+    .unwrap();
+
+    project_service
+        .update_file(&project_info, "docker-compose.yml", "linked")
+        .unwrap();
+
+    assert!(project_info.dir.join("docker-compose.yml").is_symlink());
+    assert_eq!(
+        project_service.read_file(&project_info, "compose.yml"),
+        Ok("linked".to_string())
+    );
+}
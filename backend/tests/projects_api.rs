@@ -1,5 +1,10 @@
-use common::server::{auth_test_server, test_server};
-use serde_json::json;
+use axum_test::multipart::{MultipartForm, Part};
+use backend::services::{container::ServiceInfo, notification::NotificationEvent};
+use common::server::{
+    auth_test_server, container_test_server, login, masked_test_server, notification_test_server,
+    read_only_test_server, test_server,
+};
+use serde_json::{Value, json};
 
 mod common;
 
@@ -12,11 +17,70 @@ async fn require_login() {
         server.get("/projects/project1").await,
         server.delete("/projects/project1").await,
         server.post("/projects/create/project1").await,
+        server.post("/projects/rename/project1").await,
+        server.get("/templates").await,
+        server.get("/roots").await,
+        server.get("/roots/skipped").await,
+        server.get("/projects/project1/env").await,
+        server.put("/projects/project1/env").await,
+        server.get("/projects/project1/metadata").await,
+        server.put("/projects/project1/metadata").await,
+        server.get("/projects/project1/secrets").await,
+        server.put("/projects/project1/secrets/TOKEN").await,
+        server.delete("/projects/project1/secrets/TOKEN").await,
+        server.get("/trash").await,
+        server.delete("/trash").await,
+        server.delete("/trash/1").await,
+        server.post("/trash/1/restore").await,
+        server.get("/projects/project1/export").await,
+        server.post("/projects/import?name=imported").await,
+        server.post("/projects/duplicate/project1").await,
         server.get("/projects/project1?file=compose.yml").await,
         server.delete("/projects/project1?file=compose.yml").await,
         server.post("/projects/stop/project1").await,
         server.post("/projects/start/project1").await,
         server.post("/projects/restart/project1").await,
+        server.get("/projects/project1/notifications").await,
+        server
+            .put("/projects/project1/notifications")
+            .json(&json!({ "channels": [] }))
+            .await,
+        server.get("/projects/project1/schedules").await,
+        server
+            .post("/projects/project1/schedules")
+            .json(&json!({ "cron": "0 3 * * *", "action": "update" }))
+            .await,
+        server
+            .put("/projects/project1/schedules/1")
+            .json(&json!({ "cron": "0 3 * * *", "action": "update" }))
+            .await,
+        server.delete("/projects/project1/schedules/1").await,
+        server.get("/projects/project1/updates").await,
+        server
+            .put("/projects/project1/updates")
+            .json(&json!({ "auto_update": true }))
+            .await,
+        server.get("/projects/project1/tree").await,
+        server.get("/projects/project1/files").await,
+        server
+            .post("/projects/project1/upload")
+            .multipart(MultipartForm::new().add_text("file", "content"))
+            .await,
+        server
+            .get("/projects/project1/download?file=compose.yml")
+            .await,
+        server.post("/projects/project1/dirs?dir=nginx").await,
+        server.delete("/projects/project1/dirs?dir=sub").await,
+        server.get("/projects/project1/dependencies").await,
+        server
+            .put("/projects/project1/dependencies")
+            .json(&json!({ "dependencies": [] }))
+            .await,
+        server.post("/projects/start-all").await,
+        server
+            .post("/projects/bulk")
+            .json(&json!({ "action": "start", "pattern": "*" }))
+            .await,
         server
             .post("/projects/project1?file=compose.yml")
             .json(&json!({
@@ -40,15 +104,45 @@ async fn get_projects() {
     response.assert_json(&json!([
         {
             "name": "project1",
-            "status": "running"
+            "status": "running",
+            "desired_state": null,
+            "drift": false,
+            "update_available": false,
+            "metadata": {
+                "description": "",
+                "tags": [],
+                "owner": null,
+                "icon": null,
+                "links": []
+            }
         },
         {
             "name": "project2",
-            "status": "stopped"
+            "status": "stopped",
+            "desired_state": null,
+            "drift": false,
+            "update_available": false,
+            "metadata": {
+                "description": "",
+                "tags": [],
+                "owner": null,
+                "icon": null,
+                "links": []
+            }
         },
         {
             "name": "project3",
-            "status": "running"
+            "status": "running",
+            "desired_state": null,
+            "drift": false,
+            "update_available": false,
+            "metadata": {
+                "description": "",
+                "tags": [],
+                "owner": null,
+                "icon": null,
+                "links": []
+            }
         }
     ]));
 }
@@ -63,19 +157,21 @@ async fn get_project_details() {
     response.assert_json(&json!({
         "name": "project1",
         "status": "running",
-        "files": [".env", "compose.yml"]
+        "files": [".env", "compose.yml", "sub/text.txt"]
     }));
 }
 
 #[tokio::test]
 async fn get_project_details_no_files() {
-    let (_dir, server, _token) = auth_test_server().await;
+    let (dir, server, _token) = auth_test_server().await;
+    // not listed without a compose file, but still reachable to add one
+    std::fs::create_dir(dir.path().join("empty")).unwrap();
 
-    let response = server.get("/projects/project2").await;
+    let response = server.get("/projects/empty").await;
 
     response.assert_status_ok();
     response.assert_json(&json!({
-        "name": "project2",
+        "name": "empty",
         "status": "stopped",
         "files": []
     }));
@@ -99,7 +195,7 @@ async fn stop_project() {
     response.assert_json(&json!({
         "name": "project1",
         "status": "stopped",
-        "files": [".env", "compose.yml"]
+        "files": [".env", "compose.yml", "sub/text.txt"]
     }));
     response.assert_status_ok();
 }
@@ -122,7 +218,7 @@ async fn start_project() {
     response.assert_json(&json!({
         "name": "project2",
         "status": "running",
-        "files": []
+        "files": ["compose.yml"]
     }));
     response.assert_status_ok();
 }
@@ -145,7 +241,7 @@ async fn restart_project() {
     response.assert_json(&json!({
         "name": "project1",
         "status": "running",
-        "files": [".env", "compose.yml"]
+        "files": [".env", "compose.yml", "sub/text.txt"]
     }));
     response.assert_status_ok();
 }
@@ -245,6 +341,216 @@ async fn create_new_project() {
     response.assert_status_ok();
 }
 
+#[tokio::test]
+async fn get_roots() {
+    let (dir, server, _token) = auth_test_server().await;
+
+    let response = server.get("/roots").await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!([{
+        "name": "default",
+        "path": dir.path(),
+        "permissions": { "create": true, "edit": true, "delete": true }
+    }]));
+}
+
+#[tokio::test]
+async fn get_skipped_dirs() {
+    let (dir, server, _token) = auth_test_server().await;
+    std::fs::create_dir(dir.path().join("backup")).unwrap();
+
+    let response = server.get("/roots/skipped").await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!([{
+        "root": "default",
+        "dir": dir.path().join("backup"),
+        "reason": "no_compose_file"
+    }]));
+}
+
+#[tokio::test]
+async fn create_project_from_template() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server.get("/templates").await;
+
+    response.assert_status_ok();
+    let templates = response.json::<Value>();
+    assert_eq!(templates[0]["name"], "postgres");
+    assert_eq!(templates[0]["parameters"][2]["name"], "POSTGRES_PASSWORD");
+
+    let response = server.post("/projects/create/db?template=postgres").await;
+
+    response.assert_status_bad_request();
+    response.assert_json(&json!({
+        "error": "Template postgres requires a value for POSTGRES_PASSWORD",
+        "code": "missing_template_parameter"
+    }));
+
+    let response = server
+        .post("/projects/create/db?template=postgres")
+        .json(&json!({ "POSTGRES_PASSWORD": "secret" }))
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "name": "db",
+        "status": "stopped",
+        "files": [".env", "compose.yml"]
+    }));
+}
+
+#[tokio::test]
+async fn export_and_import_project() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .get("/projects/project1/export?exclude_env=true")
+        .await;
+
+    response.assert_status_ok();
+    response.assert_header("content-type", "application/gzip");
+    response.assert_header(
+        "content-disposition",
+        "attachment; filename=\"project1.tar.gz\"",
+    );
+    let archive = response.as_bytes().clone();
+
+    let response = server
+        .post("/projects/import?name=project1")
+        .bytes(archive.clone())
+        .await;
+
+    response.assert_status_bad_request();
+
+    let response = server
+        .post("/projects/import?name=project1&conflict=rename")
+        .bytes(archive)
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "name": "project1-2",
+        "status": "stopped",
+        "files": ["compose.yml", "sub/text.txt"]
+    }));
+
+    let response = server
+        .post("/projects/import?name=broken")
+        .bytes("no archive".into())
+        .await;
+
+    response.assert_status_bad_request();
+    assert_eq!(response.json::<Value>()["code"], "invalid_archive");
+}
+
+#[tokio::test]
+async fn edit_env_file() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    server
+        .post("/projects/project1?file=compose.yml")
+        .json(&json!({ "content": "services:\n  app:\n    image: app:${TAG}\n" }))
+        .await
+        .assert_status_ok();
+
+    let response = server.get("/projects/project1/env").await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "entries": [],
+        "missing": ["TAG"],
+        "unused": []
+    }));
+
+    let response = server
+        .put("/projects/project1/env")
+        .json(&json!({ "entries": [{ "key": "TAG", "value": "1.0" }] }))
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "entries": [{ "key": "TAG", "value": "1.0" }],
+        "missing": [],
+        "unused": []
+    }));
+
+    let response = server.get("/projects/project1?file=.env").await;
+    // the line without a key is kept like a comment
+    response.assert_json(&json!({ "name": ".env", "content": ".env\nTAG=1.0\n" }));
+
+    let response = server
+        .put("/projects/project1/env?file=app.env")
+        .json(&json!({ "entries": [{ "key": "1KEY", "value": "1" }] }))
+        .await;
+
+    response.assert_status_bad_request();
+    assert_eq!(response.json::<Value>()["code"], "invalid_env_key");
+}
+
+#[tokio::test]
+async fn manage_secrets() {
+    let (dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .put("/projects/project2/secrets/DB_PASSWORD")
+        .json(&json!({ "value": "hunter2" }))
+        .await;
+
+    response.assert_status_ok();
+    let secret: Value = response.json();
+    assert_eq!(secret["name"], "DB_PASSWORD");
+    assert!(secret.get("value").is_none());
+
+    let response = server.get("/projects/project2/secrets").await;
+    response.assert_status_ok();
+    assert!(!response.text().contains("hunter2"));
+    assert_eq!(response.json::<Value>()[0]["name"], "DB_PASSWORD");
+
+    let secrets_dir = dir.path().join("project2/.containeryard-secrets");
+
+    server
+        .post("/projects/start/project2")
+        .await
+        .assert_status_ok();
+    assert_eq!(
+        std::fs::read_to_string(secrets_dir.join("DB_PASSWORD")).unwrap(),
+        "hunter2"
+    );
+
+    let response = server
+        .get("/projects/project2?file=.containeryard-secrets/DB_PASSWORD")
+        .await;
+    response.assert_status_bad_request();
+    assert_eq!(response.json::<Value>()["code"], "reserved_file");
+
+    server
+        .post("/projects/stop/project2")
+        .await
+        .assert_status_ok();
+    assert!(!secrets_dir.exists());
+
+    server
+        .delete("/projects/project2/secrets/DB_PASSWORD")
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .delete("/projects/project2/secrets/DB_PASSWORD")
+        .await;
+    response.assert_status_not_found();
+    assert_eq!(response.json::<Value>()["code"], "secret_not_found");
+
+    let response = server
+        .put("/projects/project2/secrets/db-password")
+        .json(&json!({ "value": "hunter2" }))
+        .await;
+    response.assert_status_bad_request();
+    assert_eq!(response.json::<Value>()["code"], "invalid_secret_name");
+}
+
 #[tokio::test]
 async fn create_already_existing_project() {
     let (_dir, server, _token) = auth_test_server().await;
@@ -254,6 +560,81 @@ async fn create_already_existing_project() {
     response.assert_status_bad_request();
 }
 
+#[tokio::test]
+async fn rename_running_project() {
+    let (dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .post("/projects/rename/project1")
+        .json(&json!({ "name": "renamed" }))
+        .await;
+
+    response.assert_status(axum::http::StatusCode::CONFLICT);
+    response.assert_json(&json!({
+        "error": "Project project1 is running, stop it first",
+        "code": "project_running"
+    }));
+
+    let response = server
+        .post("/projects/rename/project1?restart=true")
+        .json(&json!({ "name": "project2" }))
+        .await;
+
+    response.assert_status_bad_request();
+
+    let response = server
+        .post("/projects/rename/project1?restart=true")
+        .json(&json!({ "name": "renamed" }))
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["name"], "renamed");
+    assert_eq!(response.json::<Value>()["status"], "running");
+    assert!(!dir.path().join("project1").exists());
+}
+
+#[tokio::test]
+async fn rename_stopped_project() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .post("/projects/rename/project2")
+        .json(&json!({ "name": "renamed" }))
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "name": "renamed",
+        "status": "stopped",
+        "files": ["compose.yml"]
+    }));
+}
+
+#[tokio::test]
+async fn duplicate_project() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .post("/projects/duplicate/project1")
+        .json(&json!({ "name": "copy" }))
+        .await;
+
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["name"], "copy");
+    assert_eq!(response.json::<Value>()["status"], "stopped");
+
+    let response = server
+        .post("/projects/duplicate/project1")
+        .json(&json!({ "name": "copy" }))
+        .await;
+
+    response.assert_status_bad_request();
+    response.assert_json(&json!({
+        "error": "A Project with the name copy does already Exist",
+        "code": "project_already_exists"
+    }));
+}
+
 #[tokio::test]
 async fn delete_file_project() {
     let (_dir, server, _token) = auth_test_server().await;
@@ -276,9 +657,104 @@ async fn delete_unknown_file_project() {
 async fn delete_project() {
     let (_dir, server, _token) = auth_test_server().await;
 
+    let response = server.delete("/projects/project2").await;
+
+    response.assert_status_ok();
+}
+
+#[tokio::test]
+async fn delete_project_in_read_only_root() {
+    let (dir, server, container_service) = read_only_test_server().await;
+
+    let response = server.delete("/projects/project3?volumes=true").await;
+
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+    assert_eq!(response.json::<Value>()["code"], "root_permission_denied");
+    assert!(container_service.downs.lock().unwrap().is_empty());
+    assert!(dir.path().join("project3").exists());
+}
+
+#[tokio::test]
+async fn delete_running_project() {
+    let (dir, server, _token) = auth_test_server().await;
+
     let response = server.delete("/projects/project1").await;
 
+    response.assert_status(axum::http::StatusCode::CONFLICT);
+    assert_eq!(response.json::<Value>()["code"], "project_running");
+    assert!(dir.path().join("project1").exists());
+
+    let response = server.delete("/projects/project1?force=true").await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "name": "project1",
+        "steps": [
+            { "step": "down", "status": "ok" },
+            { "step": "trash", "status": "ok" }
+        ]
+    }));
+    assert!(!dir.path().join("project1").exists());
+}
+
+#[tokio::test]
+async fn delete_project_with_volumes() {
+    let (dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .delete("/projects/project3?volumes=true&rmi=all")
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "name": "project3",
+        "steps": [
+            { "step": "down", "status": "ok" },
+            { "step": "trash", "status": "ok" }
+        ]
+    }));
+    assert!(!dir.path().join("project3").exists());
+}
+
+#[tokio::test]
+async fn restore_and_purge_trash() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    server
+        .delete("/projects/project1?file=compose.yml")
+        .await
+        .assert_status_ok();
+    server.delete("/projects/project2").await.assert_status_ok();
+
+    let response = server.get("/trash").await;
+
+    response.assert_status_ok();
+    let entries = response.json::<Value>();
+    assert_eq!(entries[0]["project"], "project2");
+    assert_eq!(entries[0]["type"], "directory");
+    assert_eq!(entries[1]["project"], "project1");
+    assert_eq!(entries[1]["file"], "compose.yml");
+    assert_eq!(entries[1]["type"], "file");
+
+    let file_id = entries[1]["id"].as_str().unwrap();
+    let response = server.post(&format!("/trash/{}/restore", file_id)).await;
+
+    response.assert_status_ok();
+    server
+        .get("/projects/project1?file=compose.yml")
+        .await
+        .assert_status_ok();
+
+    let project_id = entries[0]["id"].as_str().unwrap();
+    let response = server.delete(&format!("/trash/{}", project_id)).await;
+
     response.assert_status_ok();
+    assert_eq!(server.get("/trash").await.json::<Value>(), json!([]));
+
+    let response = server.post(&format!("/trash/{}/restore", project_id)).await;
+
+    response.assert_status_not_found();
+    assert_eq!(response.json::<Value>()["code"], "trash_entry_not_found");
 }
 
 #[tokio::test]
@@ -289,3 +765,993 @@ async fn delete_unknown_project() {
 
     response.assert_status_not_found();
 }
+
+#[tokio::test]
+async fn get_project_notifications() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server.get("/projects/project1/notifications").await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "channels": [],
+        "events": []
+    }));
+}
+
+#[tokio::test]
+async fn update_project_notifications() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let settings = json!({
+        "channels": [
+            { "type": "webhook", "url": "http://localhost:9000/hook" },
+            { "type": "email", "to": ["ops@example.com"] },
+            { "type": "ntfy", "url": "https://ntfy.sh/yard", "token": null },
+            { "type": "gotify", "url": "https://gotify.local", "token": "abc" }
+        ],
+        "events": ["crashed", "command_failed"]
+    });
+
+    let response = server
+        .put("/projects/project1/notifications")
+        .json(&settings)
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&settings);
+
+    let response = server.get("/projects/project1/notifications").await;
+    response.assert_json(&settings);
+}
+
+#[tokio::test]
+async fn update_project_notifications_invalid_channel() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .put("/projects/project1/notifications")
+        .json(&json!({
+            "channels": [{ "type": "pigeon" }]
+        }))
+        .await;
+
+    response.assert_status_unprocessable_entity();
+}
+
+#[tokio::test]
+async fn start_stop_notifies() {
+    let (_dir, mut server, notifications) = notification_test_server();
+    login(&mut server).await;
+
+    server
+        .post("/projects/stop/project1")
+        .await
+        .assert_status_ok();
+    server
+        .post("/projects/start/project1")
+        .await
+        .assert_status_ok();
+
+    assert_eq!(
+        *notifications.sent.lock().unwrap(),
+        vec![
+            ("project1".to_string(), NotificationEvent::Stopped),
+            ("project1".to_string(), NotificationEvent::Started)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn create_project_schedule() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .post("/projects/project1/schedules")
+        .json(&json!({ "cron": "0 3 * * *", "action": "update" }))
+        .await;
+
+    response.assert_status_ok();
+
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["id"], 1);
+    assert_eq!(json["cron"], "0 3 * * *");
+    assert_eq!(json["action"], "update");
+    assert_eq!(json["enabled"], true);
+    assert_eq!(json["last_run"], serde_json::Value::Null);
+    assert!(json["next_run"].is_string());
+
+    let response = server.get("/projects/project1/schedules").await;
+    let json: serde_json::Value = response.json();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn create_project_schedule_invalid_cron() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .post("/projects/project1/schedules")
+        .json(&json!({ "cron": "61 * * * *", "action": "update" }))
+        .await;
+
+    response.assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn update_project_schedule() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    server
+        .post("/projects/project1/schedules")
+        .json(&json!({ "cron": "0 3 * * *", "action": "update" }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .put("/projects/project1/schedules/1")
+        .json(&json!({ "cron": "0 20 * * *", "action": "stop", "enabled": false }))
+        .await;
+
+    response.assert_status_ok();
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["action"], "stop");
+    assert_eq!(json["next_run"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn delete_unknown_project_schedule() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server.delete("/projects/project1/schedules/3").await;
+
+    response.assert_status_not_found();
+}
+
+#[tokio::test]
+async fn get_project_updates() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server.get("/projects/project1/updates").await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "auto_update": false,
+        "services": []
+    }));
+}
+
+#[tokio::test]
+async fn enable_project_auto_update() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .put("/projects/project1/updates")
+        .json(&json!({ "auto_update": true }))
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "auto_update": true,
+        "services": []
+    }));
+}
+
+#[tokio::test]
+async fn stop_project_sets_desired_state() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    server
+        .post("/projects/stop/project1")
+        .await
+        .assert_status_ok();
+    server
+        .post("/projects/start/project2")
+        .await
+        .assert_status_ok();
+
+    let response = server.get("/projects").await;
+    let json: serde_json::Value = response.json();
+
+    assert_eq!(json[0]["desired_state"], "stopped");
+    assert_eq!(json[0]["drift"], false);
+    assert_eq!(json[1]["desired_state"], "running");
+    assert_eq!(json[1]["drift"], false);
+    assert_eq!(json[2]["desired_state"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn update_project_dependencies() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .put("/projects/project1/dependencies")
+        .json(&json!({ "dependencies": ["project2"] }))
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "dependencies": ["project2"],
+        "dependents": []
+    }));
+
+    let response = server.get("/projects/project2/dependencies").await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "dependencies": [],
+        "dependents": ["project1"]
+    }));
+}
+
+#[tokio::test]
+async fn update_project_dependencies_cycle() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    server
+        .put("/projects/project1/dependencies")
+        .json(&json!({ "dependencies": ["project2"] }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .put("/projects/project2/dependencies")
+        .json(&json!({ "dependencies": ["project1"] }))
+        .await;
+
+    response.assert_status_conflict();
+}
+
+#[tokio::test]
+async fn update_project_dependencies_unknown() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .put("/projects/project1/dependencies")
+        .json(&json!({ "dependencies": ["project404"] }))
+        .await;
+
+    response.assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn start_project_starts_dependencies() {
+    let (_dir, mut server, notifications) = notification_test_server();
+    login(&mut server).await;
+
+    server
+        .put("/projects/project3/dependencies")
+        .json(&json!({ "dependencies": ["project2"] }))
+        .await
+        .assert_status_ok();
+    server
+        .post("/projects/start/project3")
+        .await
+        .assert_status_ok();
+
+    assert_eq!(
+        *notifications.sent.lock().unwrap(),
+        vec![
+            ("project2".to_string(), NotificationEvent::Started),
+            ("project3".to_string(), NotificationEvent::Started)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn stop_project_warns_about_dependents() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    server
+        .put("/projects/project3/dependencies")
+        .json(&json!({ "dependencies": ["project1"] }))
+        .await
+        .assert_status_ok();
+
+    let response = server.post("/projects/stop/project1").await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "name": "project1",
+        "status": "stopped",
+        "files": [".env", "compose.yml", "sub/text.txt"],
+        "warnings": ["project3 depends on project1 and is still running"]
+    }));
+}
+
+#[tokio::test]
+async fn stop_project_cascade() {
+    let (_dir, mut server, notifications) = notification_test_server();
+    login(&mut server).await;
+
+    server
+        .put("/projects/project3/dependencies")
+        .json(&json!({ "dependencies": ["project1"] }))
+        .await
+        .assert_status_ok();
+
+    let response = server.post("/projects/stop/project1?cascade=true").await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "name": "project1",
+        "status": "stopped",
+        "files": [".env", "compose.yml", "sub/text.txt"]
+    }));
+
+    assert_eq!(
+        *notifications.sent.lock().unwrap(),
+        vec![
+            ("project3".to_string(), NotificationEvent::Stopped),
+            ("project1".to_string(), NotificationEvent::Stopped)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn start_all_projects() {
+    let (_dir, mut server, notifications) = notification_test_server();
+    login(&mut server).await;
+
+    server
+        .put("/projects/project1/dependencies")
+        .json(&json!({ "dependencies": ["project2"] }))
+        .await
+        .assert_status_ok();
+
+    let response = server.post("/projects/start-all").await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!([
+        { "name": "project2", "status": "running" },
+        { "name": "project1", "status": "running" },
+        { "name": "project3", "status": "running" }
+    ]));
+
+    assert_eq!(
+        *notifications.sent.lock().unwrap(),
+        vec![("project2".to_string(), NotificationEvent::Started)]
+    );
+}
+
+#[tokio::test]
+async fn deleted_dependency() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    server
+        .put("/projects/project3/dependencies")
+        .json(&json!({ "dependencies": ["project2"] }))
+        .await
+        .assert_status_ok();
+
+    let response = server.delete("/projects/project2").await;
+
+    response.assert_status_ok();
+    assert_eq!(
+        response.json::<Value>()["warnings"],
+        json!([
+            "project3 depends on project2 and can't be started until the dependency is removed"
+        ])
+    );
+
+    // the dangling dependency doesn't affect unrelated projects
+    server
+        .post("/projects/stop/project1")
+        .await
+        .assert_status_ok();
+
+    let response = server.post("/projects/start-all").await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!([
+        { "name": "project1", "status": "running" },
+        {
+            "name": "project3",
+            "status": "stopped",
+            "error": "Project project3 depends on unknown Project project2"
+        }
+    ]));
+}
+
+#[tokio::test]
+async fn bulk_action_per_project_results() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .post("/projects/bulk")
+        .json(&json!({
+            "action": "start",
+            "projects": ["project2", "project404"],
+            "concurrency": 2
+        }))
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!([
+        { "name": "project404", "error": "Could not find Project project404" },
+        { "name": "project2", "status": "running" }
+    ]));
+}
+
+#[tokio::test]
+async fn bulk_action_pattern() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .post("/projects/bulk")
+        .json(&json!({ "action": "stop", "pattern": "project?" }))
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!([
+        { "name": "project1", "status": "stopped" },
+        { "name": "project2", "status": "stopped" },
+        { "name": "project3", "status": "stopped" }
+    ]));
+}
+
+#[tokio::test]
+async fn bulk_action_tag() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    server
+        .put("/projects/project3/metadata")
+        .json(&json!({ "tags": ["staging"] }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .post("/projects/bulk")
+        .json(&json!({ "action": "stop", "tag": "staging" }))
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!([{ "name": "project3", "status": "stopped" }]));
+}
+
+#[tokio::test]
+async fn bulk_stop_dependents_first() {
+    let (_dir, mut server, notifications) = notification_test_server();
+    login(&mut server).await;
+
+    server
+        .put("/projects/project1/dependencies")
+        .json(&json!({ "dependencies": ["project3"] }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .post("/projects/bulk")
+        .json(&json!({ "action": "stop", "projects": ["project3", "project1"] }))
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!([
+        { "name": "project1", "status": "stopped" },
+        { "name": "project3", "status": "stopped" }
+    ]));
+
+    assert_eq!(
+        *notifications.sent.lock().unwrap(),
+        vec![
+            ("project1".to_string(), NotificationEvent::Stopped),
+            ("project3".to_string(), NotificationEvent::Stopped)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn nested_project_files() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .post("/projects/project2?file=nginx/conf.d/default.conf")
+        .json(&json!({ "content": "server {}" }))
+        .await;
+    response.assert_status_ok();
+
+    let response = server
+        .get("/projects/project2?file=nginx/conf.d/default.conf")
+        .await;
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "name": "nginx/conf.d/default.conf",
+        "content": "server {}"
+    }));
+
+    let response = server.get("/projects/project2/tree").await;
+    response.assert_status_ok();
+    response.assert_json(&json!([{
+        "name": "compose.yml",
+        "path": "compose.yml",
+        "type": "file"
+    }, {
+        "name": "nginx",
+        "path": "nginx",
+        "type": "directory",
+        "children": [{
+            "name": "conf.d",
+            "path": "nginx/conf.d",
+            "type": "directory",
+            "children": [{
+                "name": "default.conf",
+                "path": "nginx/conf.d/default.conf",
+                "type": "file"
+            }]
+        }]
+    }]));
+
+    let response = server
+        .get("/projects/project2?file=nginx/../../project1/.env")
+        .await;
+    response.assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn create_delete_project_dir() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server.post("/projects/project2/dirs?dir=data").await;
+    response.assert_status_ok();
+    response.assert_json(&json!([{
+        "name": "compose.yml",
+        "path": "compose.yml",
+        "type": "file"
+    }, {
+        "name": "data",
+        "path": "data",
+        "type": "directory"
+    }]));
+
+    server
+        .delete("/projects/project2/dirs?dir=data")
+        .await
+        .assert_status_ok();
+    server
+        .delete("/projects/project2/dirs?dir=data")
+        .await
+        .assert_status_not_found();
+    server
+        .delete("/projects/project2/dirs?dir=..")
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn upload_download_binary_file() {
+    let (_dir, server, _token) = auth_test_server().await;
+    let content = vec![0x30, 0x82, 0x00, 0xff, 0xfe];
+
+    let response = server
+        .post("/projects/project2/upload?dir=certs")
+        .multipart(
+            MultipartForm::new().add_part(
+                "file",
+                Part::bytes(content.clone())
+                    .file_name("cert.der")
+                    .mime_type("application/octet-stream"),
+            ),
+        )
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!([{ "name": "certs/cert.der", "size": 5 }]));
+
+    let response = server.get("/projects/project2/tree").await;
+    response.assert_json(&json!([{
+        "name": "certs",
+        "path": "certs",
+        "type": "directory",
+        "children": [{
+            "name": "cert.der",
+            "path": "certs/cert.der",
+            "type": "file",
+            "binary": true
+        }]
+    }, {
+        "name": "compose.yml",
+        "path": "compose.yml",
+        "type": "file"
+    }]));
+
+    let response = server
+        .get("/projects/project2/download?file=certs/cert.der")
+        .await;
+    response.assert_status_ok();
+    response.assert_header("content-type", "application/x-x509-ca-cert");
+    response.assert_header("content-disposition", "attachment; filename=\"cert.der\"");
+    assert_eq!(response.as_bytes().to_vec(), content);
+
+    let response = server.get("/projects/project2?file=certs/cert.der").await;
+    response.assert_status(axum::http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn upload_outside_project() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .post("/projects/project2/upload")
+        .multipart(
+            MultipartForm::new().add_part("file", Part::bytes(vec![0]).file_name("../escape.bin")),
+        )
+        .await;
+
+    response.assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn download_unknown_file() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server.get("/projects/project1/download?file=missing").await;
+
+    response.assert_status_not_found();
+    response.assert_json(&json!({
+        "error": "Could not find file missing for project1",
+        "code": "file_not_found"
+    }));
+
+    let response = server.get("/projects/project1/download?file=sub").await;
+
+    response.assert_status_bad_request();
+    response.assert_json(&json!({
+        "error": "sub is a directory, not a file",
+        "code": "not_a_file"
+    }));
+}
+
+#[tokio::test]
+async fn get_project_files_metadata() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server.get("/projects/project1/files").await;
+
+    response.assert_status_ok();
+    let files: Vec<serde_json::Value> = response.json();
+
+    let summary: Vec<_> = files
+        .iter()
+        .map(|file| {
+            (
+                file["path"].clone(),
+                file["size"].clone(),
+                file["format"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (json!(".env"), json!(4), json!("env")),
+            (json!("compose.yml"), json!(11), json!("compose")),
+            (json!("sub/text.txt"), json!(8), json!("text")),
+        ]
+    );
+    assert_eq!(
+        files[2]["hash"],
+        "c53e1f07b32fb7a8efca70ed55e1c3f0e6528e9af0c72a6939ba86f514b781d9"
+    );
+    assert!(files[2]["modified"].is_string());
+    assert!(files[2]["mode"].is_string());
+}
+
+#[tokio::test]
+async fn update_file_with_etag() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server.get("/projects/project1?file=compose.yml").await;
+    let etag = response.header("etag");
+
+    let response = server
+        .post("/projects/project1?file=compose.yml")
+        .add_header("If-Match", etag.clone())
+        .json(&json!({ "content": "newCompose" }))
+        .await;
+
+    response.assert_status_ok();
+    let new_etag = response.header("etag");
+    assert_ne!(etag, new_etag);
+
+    let response = server
+        .post("/projects/project1?file=compose.yml")
+        .add_header("If-Match", etag)
+        .json(&json!({ "content": "otherCompose" }))
+        .await;
+
+    response.assert_status(axum::http::StatusCode::PRECONDITION_FAILED);
+    response.assert_json(&json!({
+        "error": "The file compose.yml was changed in the meantime",
+        "code": "file_changed",
+        "revision": new_etag.to_str().unwrap().trim_matches('"'),
+        "content": "newCompose"
+    }));
+}
+
+#[tokio::test]
+async fn mask_notification_credentials() {
+    let (dir, server) = masked_test_server().await;
+
+    let settings = json!({
+        "channels": [
+            { "type": "webhook", "url": "https://hooks.example.com/T000/secret" },
+            { "type": "ntfy", "url": "https://ntfy.sh/yard", "token": "tk_123" },
+            { "type": "gotify", "url": "https://gotify.local", "token": "abc" }
+        ],
+        "events": []
+    });
+    let masked = json!({
+        "channels": [
+            { "type": "webhook", "url": "********" },
+            { "type": "ntfy", "url": "https://ntfy.sh/yard", "token": "********" },
+            { "type": "gotify", "url": "https://gotify.local", "token": "********" }
+        ],
+        "events": []
+    });
+
+    let response = server
+        .put("/projects/project1/notifications")
+        .json(&settings)
+        .await;
+    response.assert_status_ok();
+    response.assert_json(&masked);
+
+    server
+        .get("/projects/project1/notifications")
+        .await
+        .assert_json(&masked);
+
+    // sending the masked form back keeps the credentials
+    let mut edited = masked.clone();
+    edited["events"] = json!(["crashed"]);
+    server
+        .put("/projects/project1/notifications")
+        .json(&edited)
+        .await
+        .assert_status_ok();
+
+    let stored = std::fs::read_to_string(dir.path().join("project1/.containeryard.json")).unwrap();
+    assert!(stored.contains("https://hooks.example.com/T000/secret"));
+    assert!(stored.contains("tk_123"));
+    assert!(!stored.contains("********"));
+}
+
+#[tokio::test]
+async fn mask_sensitive_values() {
+    let (dir, server) = masked_test_server().await;
+    std::fs::write(
+        dir.path().join("project1/.env"),
+        "DB_USER=admin\nDB_PASSWORD=hunter2\n",
+    )
+    .unwrap();
+
+    let response = server.get("/projects/project1?file=.env").await;
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "name": ".env",
+        "content": "DB_USER=admin\nDB_PASSWORD=********\n"
+    }));
+
+    let response = server.get("/projects/project1/env").await;
+    assert_eq!(
+        response.json::<Value>()["entries"],
+        json!([
+            { "key": "DB_USER", "value": "admin" },
+            { "key": "DB_PASSWORD", "value": "********" }
+        ])
+    );
+
+    let response = server.get("/projects/project1/download?file=.env").await;
+    assert_eq!(response.text(), "DB_USER=admin\nDB_PASSWORD=********\n");
+
+    let response = server.get("/projects/project1/export").await;
+    response.assert_status_forbidden();
+    assert_eq!(response.json::<Value>()["code"], "missing_permission");
+}
+
+#[tokio::test]
+async fn edit_masked_file() {
+    let (dir, server) = masked_test_server().await;
+    let env_file = dir.path().join("project1/.env");
+    std::fs::write(&env_file, "DB_USER=admin\nDB_PASSWORD=hunter2\n").unwrap();
+
+    let response = server
+        .post("/projects/project1?file=.env")
+        .json(&json!({ "content": "DB_USER=root\nDB_PASSWORD=********\n" }))
+        .await;
+
+    response.assert_status_ok();
+    response.assert_json(&json!({
+        "name": ".env",
+        "content": "DB_USER=root\nDB_PASSWORD=********\n"
+    }));
+    assert_eq!(
+        std::fs::read_to_string(&env_file).unwrap(),
+        "DB_USER=root\nDB_PASSWORD=hunter2\n"
+    );
+
+    server
+        .put("/projects/project1/env")
+        .json(&json!({ "entries": [
+            { "key": "DB_USER", "value": "admin" },
+            { "key": "DB_PASSWORD", "value": "********" },
+            { "key": "API_TOKEN", "value": "abc" }
+        ] }))
+        .await
+        .assert_status_ok();
+    assert_eq!(
+        std::fs::read_to_string(&env_file).unwrap(),
+        "DB_USER=admin\nDB_PASSWORD=hunter2\nAPI_TOKEN=abc\n"
+    );
+}
+
+#[tokio::test]
+async fn reveal_sensitive_values() {
+    let (dir, server, _token) = auth_test_server().await;
+    std::fs::write(dir.path().join("project1/.env"), "DB_PASSWORD=hunter2\n").unwrap();
+
+    let response = server.get("/projects/project1?file=.env").await;
+    response.assert_json(&json!({ "name": ".env", "content": "DB_PASSWORD=hunter2\n" }));
+}
+
+#[tokio::test]
+async fn project_metadata() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server
+        .put("/projects/project1/metadata")
+        .json(&json!({
+            "description": " Shop frontend ",
+            "tags": ["web", "Web", " prod ", ""],
+            "owner": "team-shop",
+            "links": [{ "name": "Shop", "url": "https://shop.example.com" }]
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let metadata = json!({
+        "description": "Shop frontend",
+        "tags": ["web", "prod"],
+        "owner": "team-shop",
+        "icon": null,
+        "links": [{ "name": "Shop", "url": "https://shop.example.com" }]
+    });
+    response.assert_json(&metadata);
+
+    server
+        .get("/projects/project1/metadata")
+        .await
+        .assert_json(&metadata);
+
+    server
+        .put("/projects/project3/metadata")
+        .json(&json!({ "tags": ["prod"] }))
+        .await
+        .assert_status_ok();
+
+    let projects: Value = server.get("/projects?tag=PROD").await.json();
+    let names: Vec<&str> = projects
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|project| project["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["project1", "project3"]);
+    assert_eq!(projects[0]["metadata"], metadata);
+
+    let response = server
+        .put("/projects/project1/metadata")
+        .json(&json!({ "links": [{ "name": "Shop", "url": "javascript:alert(1)" }] }))
+        .await;
+    response.assert_status_bad_request();
+    assert_eq!(response.json::<Value>()["code"], "invalid_metadata");
+}
+
+#[tokio::test]
+async fn list_projects_with_corrupt_settings() {
+    let (dir, server, _token) = auth_test_server().await;
+    std::fs::write(
+        dir.path().join("project2/.containeryard.json"),
+        "{ not json",
+    )
+    .unwrap();
+
+    server
+        .put("/projects/project1/metadata")
+        .json(&json!({ "tags": ["prod"] }))
+        .await
+        .assert_status_ok();
+
+    let response = server.get("/projects").await;
+    response.assert_status_ok();
+    let projects: Value = response.json();
+    assert_eq!(names(&projects), vec!["project1", "project2", "project3"]);
+    assert_eq!(projects[1]["metadata"]["tags"], json!([]));
+
+    let projects: Value = server.get("/projects?tag=prod").await.json();
+    assert_eq!(names(&projects), vec!["project1"]);
+
+    server
+        .post("/projects/bulk")
+        .json(&json!({ "action": "start", "tag": "prod" }))
+        .await
+        .assert_status_ok();
+}
+
+fn names(projects: &Value) -> Vec<&str> {
+    projects
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|project| project["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn filter_projects() {
+    let (_dir, server, container_service) = container_test_server().await;
+
+    container_service.set_services(
+        "project3",
+        vec![
+            ServiceInfo {
+                name: "web".to_string(),
+                image: "nginx".to_string(),
+                state: "running".to_string(),
+                exit_code: 0,
+            },
+            ServiceInfo {
+                name: "worker".to_string(),
+                image: "worker".to_string(),
+                state: "exited".to_string(),
+                exit_code: 1,
+            },
+        ],
+    );
+
+    let response = server.get("/projects?status=partial").await;
+    response.assert_status_ok();
+    assert_eq!(names(&response.json()), vec!["project3"]);
+    assert_eq!(response.json::<Value>()[0]["status"], "partial");
+    assert_eq!(response.header("x-total-count"), "1");
+
+    let response = server.get("/projects?search=PROJECT2").await;
+    assert_eq!(names(&response.json()), vec!["project2"]);
+
+    let response = server.get("/projects?status=running").await;
+    assert_eq!(names(&response.json()), vec!["project1"]);
+
+    let response = server.get("/projects?sort=status&order=desc").await;
+    assert_eq!(
+        names(&response.json()),
+        vec!["project2", "project3", "project1"]
+    );
+
+    let response = server.get("/projects?status=unknown").await;
+    response.assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn paginate_projects() {
+    let (_dir, server, _token) = auth_test_server().await;
+
+    let response = server.get("/projects?limit=2&order=desc").await;
+    assert_eq!(names(&response.json()), vec!["project3", "project2"]);
+    assert_eq!(response.header("x-total-count"), "3");
+    let cursor = response
+        .header("x-next-cursor")
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let response = server
+        .get(&format!("/projects?limit=2&order=desc&cursor={}", cursor))
+        .await;
+    assert_eq!(names(&response.json()), vec!["project1"]);
+    assert_eq!(response.header("x-total-count"), "3");
+    assert!(response.maybe_header("x-next-cursor").is_none());
+
+    let response = server.get("/projects?cursor=invalid").await;
+    response.assert_status_bad_request();
+    assert_eq!(response.json::<Value>()["code"], "invalid_cursor");
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use backend::services::{
+    container::{ContainerServiceTrait, reconciler::Reconciler},
+    notification::NotificationEvent,
+    project::{DesiredState, ProjectServiceTrait, ProjectSettings},
+};
+use common::{
+    project_service::test_project_service,
+    server::{MockContainerService, MockNotificationService},
+};
+
+mod common;
+
+#[tokio::test]
+async fn reconcile_desired_states() {
+    let (_dir, project_service) = test_project_service();
+    let project_service = Arc::new(project_service);
+    let container_service = MockContainerService::default();
+    let notification_service = MockNotificationService::default();
+
+    let project1 = project_service.project("project1").unwrap();
+    let project2 = project_service.project("project2").unwrap();
+    let project3 = project_service.project("project3").unwrap();
+
+    // project1 is running but should be stopped, project2 is stopped but should run
+    project_service
+        .set_desired_state(&project1, DesiredState::Stopped)
+        .unwrap();
+    project_service
+        .set_desired_state(&project2, DesiredState::Running)
+        .unwrap();
+
+    let reconciler = Reconciler::new(
+        project_service.clone(),
+        Arc::new(container_service.clone()),
+        Arc::new(notification_service.clone()),
+    );
+    reconciler.reconcile();
+
+    assert_eq!(container_service.is_online(&project1), Ok(false));
+    assert_eq!(container_service.is_online(&project2), Ok(true));
+    assert_eq!(container_service.is_online(&project3), Ok(true));
+
+    assert_eq!(
+        *notification_service.sent.lock().unwrap(),
+        vec![
+            ("project1".to_string(), NotificationEvent::Stopped),
+            ("project2".to_string(), NotificationEvent::Started)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn reconcile_ignores_unmanaged_projects() {
+    let (_dir, project_service) = test_project_service();
+    let project_service = Arc::new(project_service);
+    let container_service = MockContainerService::default();
+    let notification_service = MockNotificationService::default();
+
+    let project1 = project_service.project("project1").unwrap();
+    project_service
+        .set_desired_state(&project1, DesiredState::Running)
+        .unwrap();
+
+    let reconciler = Reconciler::new(
+        project_service.clone(),
+        Arc::new(container_service.clone()),
+        Arc::new(notification_service.clone()),
+    );
+    reconciler.reconcile();
+
+    let project2 = project_service.project("project2").unwrap();
+    assert_eq!(container_service.is_online(&project2), Ok(false));
+    assert!(notification_service.sent.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn reconcile_in_dependency_order() {
+    let (_dir, project_service) = test_project_service();
+    let project_service = Arc::new(project_service);
+    let container_service = MockContainerService::default();
+    let notification_service = MockNotificationService::default();
+
+    let project1 = project_service.project("project1").unwrap();
+    let project2 = project_service.project("project2").unwrap();
+
+    // project1 depends on project2, so both are started in reverse alphabetical order
+    project_service
+        .update_settings(
+            &project1,
+            &ProjectSettings {
+                dependencies: vec!["project2".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    project_service
+        .set_desired_state(&project1, DesiredState::Running)
+        .unwrap();
+    project_service
+        .set_desired_state(&project2, DesiredState::Running)
+        .unwrap();
+    container_service.stop(&project1).unwrap();
+
+    let reconciler = Reconciler::new(
+        project_service.clone(),
+        Arc::new(container_service.clone()),
+        Arc::new(notification_service.clone()),
+    );
+    reconciler.reconcile();
+
+    assert_eq!(
+        *notification_service.sent.lock().unwrap(),
+        vec![
+            ("project2".to_string(), NotificationEvent::Started),
+            ("project1".to_string(), NotificationEvent::Started)
+        ]
+    );
+}
@@ -0,0 +1,60 @@
+use backend::services::project::redaction::{MASK, RedactionPolicy};
+
+#[test]
+fn mask_env_file() {
+    let policy = RedactionPolicy::default();
+
+    let content = "# database\nPOSTGRES_USER=postgres\nPOSTGRES_PASSWORD=hunter2\nexport API_TOKEN='abc def' # comment\n#OLD_SECRET=old\nEMPTY_PASSWORD=\n";
+
+    assert_eq!(
+        policy.mask(content),
+        format!(
+            "# database\nPOSTGRES_USER=postgres\nPOSTGRES_PASSWORD={0}\nexport API_TOKEN={0} # comment\n#OLD_SECRET={0}\nEMPTY_PASSWORD=\n",
+            MASK
+        )
+    );
+}
+
+#[test]
+fn mask_compose_file() {
+    let policy = RedactionPolicy::default();
+
+    let content = "services:\n  db:\n    image: postgres\n    environment:\n      POSTGRES_PASSWORD: hunter2\n      - \"MYSQL_ROOT_PASSWORD=root\"\n      - APP_KEY=base64:abc\n    secrets:\n      - db_password\n";
+
+    assert_eq!(
+        policy.mask(content),
+        format!(
+            "services:\n  db:\n    image: postgres\n    environment:\n      POSTGRES_PASSWORD: {0}\n      - \"MYSQL_ROOT_PASSWORD={0}\"\n      - APP_KEY={0}\n    secrets:\n      - db_password\n",
+            MASK
+        )
+    );
+}
+
+#[test]
+fn custom_patterns() {
+    let policy = RedactionPolicy::new(["*_PASSWORD".to_string(), "smtp_*".to_string()]);
+
+    assert!(policy.is_sensitive("DB_PASSWORD"));
+    assert!(policy.is_sensitive("SMTP_HOST"));
+    assert!(!policy.is_sensitive("PASSWORD"));
+    assert!(!policy.is_sensitive("API_TOKEN"));
+}
+
+#[test]
+fn unmask_keeps_original_values() {
+    let policy = RedactionPolicy::default();
+
+    let original = "DB_PASSWORD=hunter2\nAPI_TOKEN=abc\nAPI_TOKEN=def\n";
+    let edited = format!(
+        "DB_USER=admin\nDB_PASSWORD={0}\nAPI_TOKEN={0}\nAPI_TOKEN=new\nNEW_SECRET={0}\n",
+        MASK
+    );
+
+    assert_eq!(
+        policy.unmask(&edited, original),
+        format!(
+            "DB_USER=admin\nDB_PASSWORD=hunter2\nAPI_TOKEN=abc\nAPI_TOKEN=new\nNEW_SECRET={}\n",
+            MASK
+        )
+    );
+}
@@ -0,0 +1,250 @@
+use std::sync::Arc;
+
+use backend::services::{
+    container::ContainerServiceTrait,
+    notification::NotificationEvent,
+    project::{DesiredState, ProjectServiceTrait, service::ProjectService},
+    schedule::{
+        NewSchedule, ScheduleAction, ScheduleServiceError, ScheduleServiceTrait,
+        service::ScheduleService,
+    },
+};
+use chrono::{Local, TimeZone};
+use common::{
+    project_service::test_project_service,
+    server::{MockContainerService, MockNotificationService},
+};
+use tempfile::TempDir;
+
+mod common;
+
+struct TestSetup {
+    _dir: TempDir,
+    project_service: Arc<ProjectService>,
+    container_service: MockContainerService,
+    notification_service: MockNotificationService,
+    schedule_service: ScheduleService,
+}
+
+fn test_schedule_service() -> TestSetup {
+    let (dir, project_service) = test_project_service();
+    let project_service = Arc::new(project_service);
+    let container_service = MockContainerService::default();
+    let notification_service = MockNotificationService::default();
+
+    let schedule_service = ScheduleService::new(
+        project_service.clone(),
+        Arc::new(container_service.clone()),
+        Arc::new(notification_service.clone()),
+    );
+
+    TestSetup {
+        _dir: dir,
+        project_service,
+        container_service,
+        notification_service,
+        schedule_service,
+    }
+}
+
+fn new_schedule(cron: &str, action: ScheduleAction) -> NewSchedule {
+    NewSchedule {
+        cron: cron.to_string(),
+        action,
+        enabled: true,
+    }
+}
+
+#[tokio::test]
+async fn add_schedule() {
+    let setup = test_schedule_service();
+    let project_info = setup.project_service.project("project1").unwrap();
+
+    let first = setup
+        .schedule_service
+        .add(
+            &project_info,
+            &new_schedule("0 3 * * *", ScheduleAction::Update),
+        )
+        .unwrap();
+    let second = setup
+        .schedule_service
+        .add(
+            &project_info,
+            &new_schedule("0 4 * * 1", ScheduleAction::Restart),
+        )
+        .unwrap();
+
+    assert_eq!(first.schedule.id, 1);
+    assert_eq!(second.schedule.id, 2);
+    assert!(first.next_run.is_some());
+
+    let schedules = setup.schedule_service.schedules(&project_info).unwrap();
+    assert_eq!(schedules, vec![first, second]);
+}
+
+#[tokio::test]
+async fn add_schedule_invalid_cron() {
+    let setup = test_schedule_service();
+    let project_info = setup.project_service.project("project1").unwrap();
+
+    let result = setup.schedule_service.add(
+        &project_info,
+        &new_schedule("every night", ScheduleAction::Stop),
+    );
+
+    assert!(matches!(
+        result,
+        Err(ScheduleServiceError::InvalidCron { cron, .. }) if cron == "every night"
+    ));
+}
+
+#[tokio::test]
+async fn update_schedule() {
+    let setup = test_schedule_service();
+    let project_info = setup.project_service.project("project1").unwrap();
+
+    setup
+        .schedule_service
+        .add(
+            &project_info,
+            &new_schedule("0 3 * * *", ScheduleAction::Update),
+        )
+        .unwrap();
+
+    let updated = setup
+        .schedule_service
+        .update(
+            &project_info,
+            1,
+            &NewSchedule {
+                cron: "0 18 * * 1-5".to_string(),
+                action: ScheduleAction::Stop,
+                enabled: false,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(updated.schedule.cron, "0 18 * * 1-5");
+    assert_eq!(updated.schedule.action, ScheduleAction::Stop);
+    assert_eq!(updated.next_run, None);
+}
+
+#[tokio::test]
+async fn update_unknown_schedule() {
+    let setup = test_schedule_service();
+    let project_info = setup.project_service.project("project1").unwrap();
+
+    let result = setup.schedule_service.update(
+        &project_info,
+        7,
+        &new_schedule("0 3 * * *", ScheduleAction::Update),
+    );
+
+    assert_eq!(
+        result,
+        Err(ScheduleServiceError::ScheduleNotFound {
+            project: "project1".to_string(),
+            id: 7
+        })
+    );
+}
+
+#[tokio::test]
+async fn remove_schedule() {
+    let setup = test_schedule_service();
+    let project_info = setup.project_service.project("project1").unwrap();
+
+    setup
+        .schedule_service
+        .add(
+            &project_info,
+            &new_schedule("0 3 * * *", ScheduleAction::Update),
+        )
+        .unwrap();
+
+    setup.schedule_service.remove(&project_info, 1).unwrap();
+
+    assert_eq!(setup.schedule_service.schedules(&project_info), Ok(vec![]));
+    assert_eq!(
+        setup.schedule_service.remove(&project_info, 1),
+        Err(ScheduleServiceError::ScheduleNotFound {
+            project: "project1".to_string(),
+            id: 1
+        })
+    );
+}
+
+#[tokio::test]
+async fn run_due_schedules() {
+    let setup = test_schedule_service();
+    let project_info = setup.project_service.project("project1").unwrap();
+
+    setup
+        .schedule_service
+        .add(
+            &project_info,
+            &new_schedule("0 18 * * *", ScheduleAction::Stop),
+        )
+        .unwrap();
+    setup
+        .schedule_service
+        .add(
+            &project_info,
+            &new_schedule("0 8 * * *", ScheduleAction::Start),
+        )
+        .unwrap();
+
+    let before = Local.with_ymd_and_hms(2025, 3, 3, 17, 59, 30).unwrap();
+    let after = Local.with_ymd_and_hms(2025, 3, 3, 18, 0, 0).unwrap();
+
+    setup.schedule_service.run_due(&before, &after);
+
+    assert_eq!(setup.container_service.is_online(&project_info), Ok(false));
+    assert_eq!(
+        *setup.notification_service.sent.lock().unwrap(),
+        vec![("project1".to_string(), NotificationEvent::Stopped)]
+    );
+
+    let settings = setup.project_service.settings(&project_info).unwrap();
+    assert_eq!(settings.desired_state, Some(DesiredState::Stopped));
+
+    let schedules = setup.schedule_service.schedules(&project_info).unwrap();
+    let last_run = schedules[0].schedule.last_run.clone().unwrap();
+    assert_eq!(last_run.timestamp, after);
+    assert!(last_run.success);
+    assert_eq!(schedules[1].schedule.last_run, None);
+}
+
+#[tokio::test]
+async fn run_due_skips_disabled_and_not_due() {
+    let setup = test_schedule_service();
+    let project_info = setup.project_service.project("project1").unwrap();
+
+    setup
+        .schedule_service
+        .add(
+            &project_info,
+            &NewSchedule {
+                cron: "0 18 * * *".to_string(),
+                action: ScheduleAction::Stop,
+                enabled: false,
+            },
+        )
+        .unwrap();
+    setup
+        .schedule_service
+        .add(
+            &project_info,
+            &new_schedule("0 19 * * *", ScheduleAction::Stop),
+        )
+        .unwrap();
+
+    let before = Local.with_ymd_and_hms(2025, 3, 3, 17, 59, 30).unwrap();
+    let after = Local.with_ymd_and_hms(2025, 3, 3, 18, 0, 0).unwrap();
+
+    setup.schedule_service.run_due(&before, &after);
+
+    assert_eq!(setup.container_service.is_online(&project_info), Ok(true));
+    assert!(setup.notification_service.sent.lock().unwrap().is_empty());
+}
@@ -0,0 +1,227 @@
+use std::{collections::HashMap, sync::Arc};
+
+use backend::services::{
+    container::{ContainerServiceTrait, ServiceInfo},
+    notification::NotificationEvent,
+    project::{ProjectServiceTrait, service::ProjectService},
+    update::{
+        ImageReference, UpdateServiceError, UpdateServiceTrait, registry::RegistryClient,
+        service::UpdateService,
+    },
+};
+use common::{
+    project_service::test_project_service,
+    server::{MockContainerService, MockNotificationService},
+    sinks::registry_stub,
+};
+use tempfile::TempDir;
+
+mod common;
+
+const OLD_DIGEST: &str = "sha256:0000000000000000000000000000000000000000000000000000000000000000";
+const NEW_DIGEST: &str = "sha256:1111111111111111111111111111111111111111111111111111111111111111";
+
+struct TestSetup {
+    _dir: TempDir,
+    image: String,
+    project_service: Arc<ProjectService>,
+    container_service: MockContainerService,
+    notification_service: MockNotificationService,
+    update_service: UpdateService,
+}
+
+async fn test_update_service(require_token: bool) -> TestSetup {
+    let addr = registry_stub(
+        HashMap::from([("team/app:1.0".to_string(), NEW_DIGEST.to_string())]),
+        require_token,
+    )
+    .await;
+    let image = format!("{}/team/app:1.0", addr);
+
+    let (dir, project_service) = test_project_service();
+    let project_service = Arc::new(project_service);
+    let container_service = MockContainerService::default();
+    let notification_service = MockNotificationService::default();
+
+    container_service.set_services(
+        "project1",
+        vec![ServiceInfo {
+            name: "app".to_string(),
+            image: image.clone(),
+            state: "running".to_string(),
+            exit_code: 0,
+        }],
+    );
+
+    let update_service = UpdateService::new(
+        project_service.clone(),
+        Arc::new(container_service.clone()),
+        Arc::new(notification_service.clone()),
+        RegistryClient::new(vec![addr.to_string()]),
+    );
+
+    TestSetup {
+        _dir: dir,
+        image,
+        project_service,
+        container_service,
+        notification_service,
+        update_service,
+    }
+}
+
+#[tokio::test]
+async fn parse_image_references() {
+    assert_eq!(
+        ImageReference::parse("nginx"),
+        Ok(ImageReference {
+            registry: "registry-1.docker.io".to_string(),
+            repository: "library/nginx".to_string(),
+            tag: "latest".to_string(),
+            digest: None,
+        })
+    );
+
+    assert_eq!(
+        ImageReference::parse("ghcr.io/tobinio/container-yard-backend:0.1.0"),
+        Ok(ImageReference {
+            registry: "ghcr.io".to_string(),
+            repository: "tobinio/container-yard-backend".to_string(),
+            tag: "0.1.0".to_string(),
+            digest: None,
+        })
+    );
+
+    assert_eq!(
+        ImageReference::parse("localhost:5000/app"),
+        Ok(ImageReference {
+            registry: "localhost:5000".to_string(),
+            repository: "app".to_string(),
+            tag: "latest".to_string(),
+            digest: None,
+        })
+    );
+
+    assert_eq!(
+        ImageReference::parse(&format!("grafana/grafana:11@{}", OLD_DIGEST)),
+        Ok(ImageReference {
+            registry: "registry-1.docker.io".to_string(),
+            repository: "grafana/grafana".to_string(),
+            tag: "11".to_string(),
+            digest: Some(OLD_DIGEST.to_string()),
+        })
+    );
+
+    assert_eq!(
+        ImageReference::parse("nginx:"),
+        Err(UpdateServiceError::InvalidImage("nginx:".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn remote_digest_with_token() {
+    let setup = test_update_service(true).await;
+
+    let reference = ImageReference::parse(&setup.image).unwrap();
+    let registry_addr = reference.registry.clone();
+    let digest = RegistryClient::new(vec![registry_addr])
+        .remote_digest(&reference)
+        .await;
+
+    assert_eq!(digest, Ok(NEW_DIGEST.to_string()));
+}
+
+#[tokio::test]
+async fn detect_outdated_image() {
+    let setup = test_update_service(false).await;
+    setup
+        .container_service
+        .set_image_digests(&setup.image, vec![format!("team/app@{}", OLD_DIGEST)]);
+
+    let project_info = setup.project_service.project("project1").unwrap();
+    let updates = setup.update_service.check_project(&project_info).await;
+
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].service, "app");
+    assert_eq!(updates[0].local_digest, Some(OLD_DIGEST.to_string()));
+    assert_eq!(updates[0].remote_digest, Some(NEW_DIGEST.to_string()));
+    assert!(updates[0].update_available);
+
+    assert_eq!(setup.update_service.updates(&project_info), updates);
+    assert!(setup.update_service.update_available(&project_info));
+}
+
+#[tokio::test]
+async fn detect_current_image() {
+    let setup = test_update_service(true).await;
+    setup
+        .container_service
+        .set_image_digests(&setup.image, vec![format!("team/app@{}", NEW_DIGEST)]);
+
+    let project_info = setup.project_service.project("project1").unwrap();
+    let updates = setup.update_service.check_project(&project_info).await;
+
+    assert!(!updates[0].update_available);
+    assert_eq!(updates[0].error, None);
+}
+
+#[tokio::test]
+async fn unknown_tag_reports_error() {
+    let setup = test_update_service(false).await;
+    let image = setup.image.replace(":1.0", ":2.0");
+    setup.container_service.set_services(
+        "project1",
+        vec![ServiceInfo {
+            name: "app".to_string(),
+            image,
+            state: "running".to_string(),
+            exit_code: 0,
+        }],
+    );
+
+    let project_info = setup.project_service.project("project1").unwrap();
+    let updates = setup.update_service.check_project(&project_info).await;
+
+    assert!(!updates[0].update_available);
+    assert!(updates[0].error.is_some());
+}
+
+#[tokio::test]
+async fn auto_update_outdated_project() {
+    let setup = test_update_service(false).await;
+    setup
+        .container_service
+        .set_image_digests(&setup.image, vec![format!("team/app@{}", OLD_DIGEST)]);
+
+    let project_info = setup.project_service.project("project1").unwrap();
+    let mut settings = setup.project_service.settings(&project_info).unwrap();
+    settings.auto_update = true;
+    setup
+        .project_service
+        .update_settings(&project_info, &settings)
+        .unwrap();
+
+    setup.container_service.stop(&project_info).unwrap();
+    setup.update_service.check_all().await;
+
+    assert_eq!(setup.container_service.is_online(&project_info), Ok(true));
+    assert_eq!(
+        *setup.notification_service.sent.lock().unwrap(),
+        vec![("project1".to_string(), NotificationEvent::Started)]
+    );
+}
+
+#[tokio::test]
+async fn no_auto_update_by_default() {
+    let setup = test_update_service(false).await;
+    setup
+        .container_service
+        .set_image_digests(&setup.image, vec![format!("team/app@{}", OLD_DIGEST)]);
+
+    let project_info = setup.project_service.project("project1").unwrap();
+    setup.container_service.stop(&project_info).unwrap();
+    setup.update_service.check_all().await;
+
+    assert_eq!(setup.container_service.is_online(&project_info), Ok(false));
+    assert!(setup.update_service.update_available(&project_info));
+}